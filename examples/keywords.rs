@@ -0,0 +1,63 @@
+// 用 `_!_` 语料的关键词列评估关键词提取：标题建索引得到 IDF，
+// 再对每个标题提取关键词，和语料中的关键词比较准确率和召回率
+// cargo run --example keywords -- ./data
+use rust_starter::crawler::read_file_data_from;
+use rust_starter::nlpcut::KeywordMethod;
+use rust_starter::search::{DocSource, IndexDoc, SearchService};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+const TOP_K: usize = 5;
+const MAX_DOCS: usize = 10_000;
+
+fn main() {
+    let mut roots: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if roots.is_empty() {
+        roots.push(PathBuf::from("./data"));
+    }
+    let data: Vec<(String, String)> = read_file_data_from(&roots)
+        .into_iter()
+        .filter(|(_, keywords)| !keywords.trim().is_empty())
+        .take(MAX_DOCS)
+        .collect();
+    if data.is_empty() {
+        eprintln!("no corpus lines with keywords under {:?}", roots);
+        return;
+    }
+
+    let service = SearchService::create_in_ram().unwrap();
+    let docs: Vec<IndexDoc> = data
+        .iter()
+        .enumerate()
+        .map(|(id, (title, _))| IndexDoc {
+            id: id as u64,
+            source: DocSource::Api,
+            title: title.clone(),
+            body: String::new(),
+            url: String::new(),
+            doc_type: String::new(),
+        })
+        .collect();
+    service.add_all(&docs).unwrap();
+
+    for method in [KeywordMethod::TfIdf, KeywordMethod::TextRank] {
+        let (mut hits, mut extracted, mut expected) = (0, 0, 0);
+        for (title, keywords) in &data {
+            let gold: HashSet<&str> = keywords.split(',').map(str::trim).collect();
+            let found = service.keywords(title, TOP_K, method).unwrap();
+            hits += found
+                .iter()
+                .filter(|keyword| gold.contains(keyword.word.as_str()))
+                .count();
+            extracted += found.len();
+            expected += gold.len();
+        }
+        println!(
+            "{:?}: docs={} precision={:.3} recall={:.3}",
+            method,
+            data.len(),
+            hits as f64 / extracted.max(1) as f64,
+            hits as f64 / expected.max(1) as f64,
+        );
+    }
+}
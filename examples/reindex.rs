@@ -0,0 +1,103 @@
+// 按当前 schema 重建搜索索引：数据库文档从数据库重新读取，/insert 写入的文档从旧索引的存储字段恢复。
+// 新索引先写到 <index.path>.reindex，完成后替换旧目录。schema 版本不一致、服务无法启动时运行，
+// 参数和启动服务时相同：
+// cargo run --release --example reindex -- -c ./config.toml
+use rust_starter::config::Config;
+use rust_starter::db::{self, ListQuery};
+use rust_starter::search::{DocSource, IndexDoc, SearchService, StoredDocs};
+use rust_starter::server::configure_search;
+use rust_starter::server::handlers::index_doc;
+use rust_starter::Result;
+use std::collections::HashSet;
+use std::path::Path;
+
+const PAGE_SIZE: i64 = 500;
+// 每批提交一次；重复检测只和已提交的文档比较，同一批里的重复不会归到一组
+const BATCH_SIZE: usize = 1000;
+
+#[tokio::main]
+async fn main() {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("configuration error: {}", err);
+            std::process::exit(2);
+        }
+    };
+    let Some(path) = config.index.path.clone() else {
+        eprintln!("index.path is not set, the server uses a temporary index that needs no rebuild");
+        std::process::exit(2);
+    };
+    if let Err(err) = reindex(&config, &path).await {
+        eprintln!("reindex error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn reindex(config: &Config, path: &Path) -> Result<()> {
+    let store = db::connect(&config.database).await?;
+    let mut db_docs = Vec::new();
+    let mut after_id = None;
+    loop {
+        let page = store
+            .list(ListQuery {
+                after_id,
+                limit: Some(PAGE_SIZE),
+                ..Default::default()
+            })
+            .await?;
+        db_docs.extend(page.docs.iter().map(index_doc));
+        match page.next_cursor {
+            Some(cursor) => after_id = Some(cursor),
+            None => break,
+        }
+    }
+
+    let stored = if path.join("meta.json").is_file() {
+        SearchService::stored_docs(path)?
+    } else {
+        StoredDocs {
+            docs: Vec::new(),
+            has_source: true,
+        }
+    };
+    // 没有 source 字段的旧索引里，数据库文档也按数据库 id 存着，以数据库中的为准
+    let db_ids: HashSet<u64> = db_docs.iter().map(|doc| doc.id).collect();
+    let api_docs: Vec<IndexDoc> = stored
+        .docs
+        .into_iter()
+        .filter(|doc| {
+            doc.source == DocSource::Api && (stored.has_source || !db_ids.contains(&doc.id))
+        })
+        .collect();
+
+    let staging = path.with_extension("reindex");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    let search = configure_search(config, SearchService::open_or_create(&staging)?)?;
+    for batch in db_docs
+        .chunks(BATCH_SIZE)
+        .chain(api_docs.chunks(BATCH_SIZE))
+    {
+        search.add_all(batch)?;
+    }
+    search.shutdown()?;
+    drop(search);
+
+    let backup = path.with_extension("old");
+    if path.exists() {
+        std::fs::rename(path, &backup)?;
+    }
+    std::fs::rename(&staging, path)?;
+    if backup.exists() {
+        std::fs::remove_dir_all(&backup)?;
+    }
+    println!(
+        "reindexed {} database documents and {} inserted documents into {}",
+        db_docs.len(),
+        api_docs.len(),
+        path.display()
+    );
+    Ok(())
+}
@@ -1,5 +1,15 @@
+use rust_starter::config::Config;
 use rust_starter::search::engine::exc_search;
+
+// 语料目录来自配置文件、APP_CRAWLER_ROOTS 或 --crawler-root
 #[tokio::main]
 async fn main() {
-    exc_search();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("configuration error: {}", err);
+            std::process::exit(2);
+        }
+    };
+    exc_search(&config.crawler.roots);
 }
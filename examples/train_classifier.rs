@@ -0,0 +1,62 @@
+// 用 `_!_` 语料的分类列训练文档分类器：每十行留出一行评估准确率，其余用于训练，
+// 模型保存到第一个参数指定的文件，之后在配置的 [classifier] path 中使用
+// cargo run --release --example train_classifier -- ./classifier.json ./data
+use jieba_rs::Jieba;
+use rust_starter::crawler::read_corpus_from;
+use rust_starter::nlpcut::Classifier;
+use std::path::PathBuf;
+
+// 总共出现少于两次的词不进入词表，模型文件小很多，准确率几乎不变
+const MIN_COUNT: usize = 2;
+const HOLDOUT_EVERY: usize = 10;
+
+fn main() {
+    let mut args = std::env::args().skip(1).map(PathBuf::from);
+    let output = args
+        .next()
+        .unwrap_or_else(|| PathBuf::from("./classifier.json"));
+    let mut roots: Vec<PathBuf> = args.collect();
+    if roots.is_empty() {
+        roots.push(PathBuf::from("./data"));
+    }
+    let samples: Vec<(String, String)> = read_corpus_from(&roots)
+        .into_iter()
+        .filter_map(|line| Some((line.category?, line.title)))
+        .collect();
+    if samples.is_empty() {
+        eprintln!("no labelled corpus lines under {:?}", roots);
+        std::process::exit(1);
+    }
+
+    let jieba = Jieba::new();
+    let (train, test): (Vec<_>, Vec<_>) = samples
+        .iter()
+        .enumerate()
+        .partition(|(i, _)| i % HOLDOUT_EVERY != 0);
+    let classifier = Classifier::train(
+        &jieba,
+        train.iter().map(|(_, (label, title))| (label, title)),
+        MIN_COUNT,
+    );
+    let correct = test
+        .iter()
+        .filter(|(_, (label, title))| {
+            classifier
+                .predict(&jieba, title)
+                .is_some_and(|prediction| &prediction.label == label)
+        })
+        .count();
+    println!(
+        "labels={} train={} test={} accuracy={:.3}",
+        classifier.labels().len(),
+        train.len(),
+        test.len(),
+        correct as f64 / test.len().max(1) as f64,
+    );
+
+    if let Err(err) = classifier.save(&output) {
+        eprintln!("cannot write {}: {}", output.display(), err);
+        std::process::exit(1);
+    }
+    println!("model saved to {}", output.display());
+}
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE docs (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  title VARCHAR NOT NULL,
+  url VARCHAR NOT NULL,
+  content TEXT NOT NULL,
+  doc_type VARCHAR NOT NULL,
+  published BOOLEAN NOT NULL DEFAULT FALSE
+)
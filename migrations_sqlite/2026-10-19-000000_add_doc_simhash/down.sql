@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE docs DROP COLUMN duplicate_of;
+ALTER TABLE docs DROP COLUMN simhash;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE docs ADD COLUMN simhash BIGINT;
+ALTER TABLE docs ADD COLUMN duplicate_of INTEGER;
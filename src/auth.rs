@@ -0,0 +1,192 @@
+// API key / JWT 认证。搜索等只读接口公开，写接口需要 write 角色，管理接口需要 admin 角色
+use crate::config::{AuthConfig, ConfigError};
+use crate::error::AppError;
+use crate::server::AppState;
+use crate::Result;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde_derive::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// admin 包含 write，write 包含 read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Read,
+    Write,
+    Admin,
+}
+
+// 认证通过的调用方，放在 request extensions 中
+#[derive(Debug, Clone)]
+pub struct Principal {
+    // API key 的 name 或 JWT 的 sub
+    pub id: String,
+    pub roles: Vec<Role>,
+}
+
+impl Principal {
+    pub fn has(&self, role: Role) -> bool {
+        self.roles.iter().any(|r| *r >= role)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    roles: Vec<Role>,
+}
+
+pub struct Authenticator {
+    enabled: bool,
+    allow_anonymous: bool,
+    // key 的 SHA-256 摘要 -> 调用方
+    api_keys: HashMap<Vec<u8>, Principal>,
+    jwt: Option<(DecodingKey, Validation)>,
+}
+
+impl Authenticator {
+    // 配置已经通过 Config::validate 校验
+    pub fn from_config(config: &AuthConfig) -> std::result::Result<Self, ConfigError> {
+        let api_keys = config
+            .api_keys
+            .iter()
+            .map(|key| {
+                let digest = hex::decode(&key.key_sha256).unwrap_or_default();
+                let principal = Principal {
+                    id: key.name.clone(),
+                    roles: key.roles.clone(),
+                };
+                (digest, principal)
+            })
+            .collect();
+        let jwt = match &config.jwt {
+            Some(jwt) => {
+                let (key, algorithm) = match (&jwt.secret, &jwt.public_key) {
+                    (Some(secret), _) => (
+                        DecodingKey::from_secret(secret.as_bytes()),
+                        Algorithm::HS256,
+                    ),
+                    (None, Some(path)) => {
+                        let pem = std::fs::read(path).map_err(|source| ConfigError::Read {
+                            path: path.clone(),
+                            source,
+                        })?;
+                        let key = DecodingKey::from_rsa_pem(&pem).map_err(|err| {
+                            ConfigError::Invalid {
+                                key: "auth.jwt.public_key",
+                                reason: err.to_string(),
+                            }
+                        })?;
+                        (key, Algorithm::RS256)
+                    }
+                    (None, None) => {
+                        return Err(ConfigError::Invalid {
+                            key: "auth.jwt",
+                            reason: "`secret` or `public_key` is required".to_string(),
+                        })
+                    }
+                };
+                let mut validation = Validation::new(algorithm);
+                if let Some(issuer) = &jwt.issuer {
+                    validation.set_issuer(&[issuer]);
+                }
+                match &jwt.audience {
+                    Some(audience) => validation.set_audience(&[audience]),
+                    None => validation.validate_aud = false,
+                }
+                Some((key, validation))
+            }
+            None => None,
+        };
+        Ok(Self {
+            enabled: config.enabled(),
+            allow_anonymous: config.allow_anonymous,
+            api_keys,
+            jwt,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn allow_anonymous(&self) -> bool {
+        self.allow_anonymous
+    }
+
+    // 从 X-Api-Key 或 Authorization: Bearer 中识别调用方，没有凭证时返回 None
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<Principal>> {
+        let api_key = headers.get("x-api-key").and_then(|v| v.to_str().ok());
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        let credential = match api_key.or(bearer) {
+            Some(credential) => credential.trim(),
+            None => return Ok(None),
+        };
+
+        let digest = Sha256::digest(credential.as_bytes());
+        if let Some(principal) = self.api_keys.get(digest.as_slice()) {
+            return Ok(Some(principal.clone()));
+        }
+        if let (Some(token), Some((key, validation))) = (bearer, &self.jwt) {
+            let claims = jsonwebtoken::decode::<Claims>(token.trim(), key, validation)
+                .map_err(|err| AppError::unauthorized_msg(&format!("invalid token: {}", err)))?
+                .claims;
+            return Ok(Some(Principal {
+                id: claims.sub,
+                roles: claims.roles,
+            }));
+        }
+        Err(AppError::unauthorized_msg("invalid credentials"))
+    }
+
+    // 没有凭证时只有 allow_anonymous 才放行；携带的凭证总是要校验
+    pub fn authorize(&self, headers: &HeaderMap, role: Role) -> Result<Option<Principal>> {
+        match self.authenticate(headers)? {
+            Some(principal) if principal.has(role) => Ok(Some(principal)),
+            Some(principal) => Err(AppError::forbidden_msg(&format!(
+                "{} does not have the {:?} role",
+                principal.id, role
+            ))),
+            None if self.allow_anonymous => Ok(None),
+            None => Err(AppError::unauthorized_msg("credentials are required")),
+        }
+    }
+}
+
+async fn require<B>(
+    state: AppState,
+    mut req: Request<B>,
+    next: Next<B>,
+    role: Role,
+) -> Result<Response> {
+    if let Some(principal) = state.auth.authorize(req.headers(), role)? {
+        tracing::debug!(principal = %principal.id, "authorized");
+        req.extensions_mut().insert(principal);
+    }
+    Ok(next.run(req).await)
+}
+
+pub async fn require_write<B>(
+    State(state): State<AppState>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response> {
+    require(state, req, next, Role::Write).await
+}
+
+pub async fn require_admin<B>(
+    State(state): State<AppState>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response> {
+    require(state, req, next, Role::Admin).await
+}
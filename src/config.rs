@@ -0,0 +1,789 @@
+// 配置来源优先级：默认值 < TOML 配置文件 < 环境变量 < 命令行参数
+use crate::auth::Role;
+use crate::nlpcut::simhash;
+use crate::search::{
+    Bm25Params, Dedup, FieldBoosts, FieldNormalizers, PopularThreshold, QueryLimits, Relevance,
+};
+use clap::Parser;
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// tantivy 的 IndexWriter 至少需要 15MB 内存
+const MIN_WRITER_MEMORY: usize = 15_000_000;
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const DEFAULT_SQLITE_PATH: &str = "docs.db";
+
+#[derive(Debug, Default, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path of the TOML config file (default: ./config.toml if present)
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    #[arg(long)]
+    pub bind: Option<SocketAddr>,
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Seconds to wait for in-flight requests on SIGINT/SIGTERM
+    #[arg(long, value_name = "SECS")]
+    pub shutdown_timeout: Option<u64>,
+    /// Document store backend, inferred from the database url when omitted
+    #[arg(long, value_enum)]
+    pub backend: Option<StoreBackend>,
+    /// postgres:// url, or a SQLite file path (optionally prefixed with sqlite://)
+    #[arg(long)]
+    pub database_url: Option<String>,
+    #[arg(long)]
+    pub pool_size: Option<usize>,
+    #[arg(long)]
+    pub index_path: Option<PathBuf>,
+    /// IndexWriter memory budget in bytes
+    #[arg(long)]
+    pub writer_memory: Option<usize>,
+    #[arg(long)]
+    pub default_page_size: Option<usize>,
+    #[arg(long)]
+    pub max_page_size: Option<usize>,
+    /// Largest offset + limit a search may request
+    #[arg(long)]
+    pub max_result_window: Option<usize>,
+    /// jieba user dictionary, can be repeated
+    #[arg(long = "dict", value_name = "FILE")]
+    pub dict_paths: Vec<PathBuf>,
+    /// directory of `_!_` corpus files, can be repeated
+    #[arg(long = "crawler-root", value_name = "DIR")]
+    pub crawler_roots: Vec<PathBuf>,
+    #[arg(long, value_enum)]
+    pub log_format: Option<LogFormat>,
+    /// Log filter directives, e.g. `info,rust_starter=debug` (RUST_LOG takes precedence)
+    #[arg(long, value_name = "FILTER")]
+    pub log_filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub index: IndexConfig,
+    pub pagination: PageConfig,
+    pub tokenizer: TokenizerConfig,
+    pub crawler: CrawlerConfig,
+    pub log: LogConfig,
+    pub auth: AuthConfig,
+    pub limits: LimitsConfig,
+    pub search: SearchConfig,
+    pub classifier: ClassifierConfig,
+    pub dedup: Dedup,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: SocketAddr,
+    // 收到退出信号后等待处理中请求的最长时间（秒）
+    pub shutdown_timeout: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            shutdown_timeout: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    Postgres,
+    Sqlite,
+}
+
+impl FromStr for StoreBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(StoreBackend::Postgres),
+            "sqlite" => Ok(StoreBackend::Sqlite),
+            _ => Err("expected `postgres` or `sqlite`".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub backend: Option<StoreBackend>,
+    pub url: Option<String>,
+    pub pool_size: usize,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            url: None,
+            pool_size: 16,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    // 没有指定 backend 时，postgres:// 地址使用 Postgres，其余（包括没有配置 url）使用内嵌的 SQLite
+    pub fn backend(&self) -> StoreBackend {
+        match (self.backend, self.url.as_deref()) {
+            (Some(backend), _) => backend,
+            (None, Some(url)) if is_postgres_url(url) => StoreBackend::Postgres,
+            (None, _) => StoreBackend::Sqlite,
+        }
+    }
+
+    pub fn sqlite_path(&self) -> String {
+        match self.url.as_deref() {
+            Some(url) if !url.is_empty() => {
+                url.strip_prefix("sqlite://").unwrap_or(url).to_string()
+            }
+            _ => DEFAULT_SQLITE_PATH.to_string(),
+        }
+    }
+}
+
+fn is_postgres_url(url: &str) -> bool {
+    url.starts_with("postgres://") || url.starts_with("postgresql://")
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexConfig {
+    // 为空时索引放在临时目录，进程退出后丢失
+    pub path: Option<PathBuf>,
+    pub writer_memory: usize,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            writer_memory: crate::search::service::DEFAULT_WRITER_MEMORY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PageConfig {
+    pub default_page_size: usize,
+    pub max_page_size: usize,
+    // offset + limit 的上限，翻页太深时 TopDocs 需要分配 offset + limit 大小的堆
+    pub max_result_window: usize,
+}
+
+impl Default for PageConfig {
+    fn default() -> Self {
+        Self {
+            default_page_size: 10,
+            max_page_size: 100,
+            max_result_window: 10_000,
+        }
+    }
+}
+
+impl PageConfig {
+    pub fn clamp(&self, limit: Option<usize>) -> usize {
+        limit
+            .unwrap_or(self.default_page_size)
+            .clamp(1, self.max_page_size)
+    }
+
+    pub fn check_window(&self, offset: usize, limit: usize) -> Result<(), String> {
+        match offset.checked_add(limit) {
+            Some(end) if end <= self.max_result_window => Ok(()),
+            _ => Err(format!(
+                "offset + limit must not exceed {}",
+                self.max_result_window
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenizerConfig {
+    pub dict_paths: Vec<PathBuf>,
+    // 各字段的归一化（繁简、全半角、NFKC、小写），默认全部开启
+    pub normalize: FieldNormalizers,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlerConfig {
+    pub roots: Vec<PathBuf>,
+}
+
+impl Default for CrawlerConfig {
+    fn default() -> Self {
+        Self {
+            roots: vec![PathBuf::from("./data")],
+        }
+    }
+}
+
+// 用 `cargo run --release --example train_classifier` 训练的模型文件；
+// 没有配置时 /nlp/classify 不可用，/insert_doc 也不自动填写 doc_type
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClassifierConfig {
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected `text` or `json`".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    // RUST_LOG 风格的过滤规则，设置了 RUST_LOG 环境变量时以环境变量为准
+    pub filter: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            filter: "info".to_string(),
+        }
+    }
+}
+
+// 写接口和管理接口默认需要凭证；没有配置任何 API key 或 JWT 时一律返回 401，
+// 除非显式设置 allow_anonymous = true（只适合本地开发）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub api_keys: Vec<ApiKeyConfig>,
+    pub jwt: Option<JwtConfig>,
+    // 没有携带凭证的请求也可以调用写接口和管理接口
+    pub allow_anonymous: bool,
+}
+
+impl AuthConfig {
+    pub fn enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    // key 的 SHA-256 十六进制摘要，配置里不保存明文：echo -n "$KEY" | sha256sum
+    pub key_sha256: String,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    // HS256 共享密钥
+    pub secret: Option<String>,
+    // RS256 公钥 PEM 文件
+    pub public_key: Option<PathBuf>,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    // 各字段的默认权重，请求中可以用 boost=title^3 body^1 覆盖
+    pub boosts: FieldBoosts,
+    // 多个词项默认用 AND 连接
+    pub conjunction: bool,
+    pub bm25: Bm25Params,
+    // 命名的同义词词典（Solr 格式），搜索时用 synonyms=名称 选择，默认使用 default
+    pub synonyms: HashMap<String, PathBuf>,
+    // 热门查询至少被搜索 min_count 次、来自 min_clients 个客户端才出现在 /suggest 中
+    pub popular: PopularThreshold,
+}
+
+impl SearchConfig {
+    pub fn relevance(&self) -> Relevance {
+        Relevance {
+            boosts: self.boosts,
+            conjunction: self.conjunction,
+            bm25: self.bm25,
+        }
+    }
+}
+
+// 令牌桶：每秒补充 rate 个令牌，最多攒 burst 个；rate 为 0 时不限流
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Quota {
+    pub rate: f64,
+    pub burst: u32,
+}
+
+impl Quota {
+    pub fn enabled(&self) -> bool {
+        self.rate > 0.0
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    // 没有凭证的请求按客户端 IP 限流
+    pub per_ip: Quota,
+    // 带 API key 或 JWT 的请求按调用方限流
+    pub per_key: Quota,
+    // 请求体大小上限（字节），批量导入接口使用 bulk_body_bytes
+    pub body_bytes: usize,
+    pub bulk_body_bytes: usize,
+    // 查询字符串的最大字符数和最多词项数
+    pub max_query_len: usize,
+    pub max_query_terms: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            per_ip: Quota {
+                rate: 20.0,
+                burst: 40,
+            },
+            per_key: Quota {
+                rate: 100.0,
+                burst: 200,
+            },
+            body_bytes: 1024 * 1024,
+            bulk_body_bytes: 32 * 1024 * 1024,
+            max_query_len: 256,
+            max_query_terms: 32,
+        }
+    }
+}
+
+impl LimitsConfig {
+    pub fn query_limits(&self) -> QueryLimits {
+        QueryLimits {
+            max_len: self.max_query_len,
+            max_terms: self.max_query_terms,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    Env {
+        key: &'static str,
+        value: String,
+        reason: String,
+    },
+    Invalid {
+        key: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, source } => {
+                write!(f, "cannot read config file {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "invalid config file {}: {}", path.display(), source)
+            }
+            ConfigError::Env { key, value, reason } => {
+                write!(
+                    f,
+                    "invalid environment variable {}={:?}: {}",
+                    key, value, reason
+                )
+            }
+            ConfigError::Invalid { key, reason } => write!(f, "invalid `{}`: {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn invalid(key: &'static str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        key,
+        reason: reason.into(),
+    }
+}
+
+fn parse_env<T>(
+    env: &dyn Fn(&str) -> Option<String>,
+    key: &'static str,
+) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match env(key) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|err: T::Err| ConfigError::Env {
+                key,
+                reason: err.to_string(),
+                value,
+            }),
+        None => Ok(None),
+    }
+}
+
+fn env_paths(env: &dyn Fn(&str) -> Option<String>, key: &str) -> Option<Vec<PathBuf>> {
+    env(key).map(|value| {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    })
+}
+
+impl Config {
+    // 读取命令行参数和进程环境变量
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_sources(Cli::parse(), &|key| std::env::var(key).ok())
+    }
+
+    pub fn from_sources(
+        cli: Cli,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let path = cli
+            .config
+            .clone()
+            .or_else(|| env("APP_CONFIG").map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(env)?;
+        config.apply_cli(cli);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&content).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    fn apply_env(&mut self, env: &dyn Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        if let Some(bind) = parse_env(env, "APP_BIND")? {
+            self.server.bind = bind;
+        }
+        // 兼容旧的 PORT 环境变量
+        if let Some(port) = parse_env(env, "PORT")? {
+            self.server.bind.set_port(port);
+        }
+        if let Some(timeout) = parse_env(env, "APP_SHUTDOWN_TIMEOUT")? {
+            self.server.shutdown_timeout = timeout;
+        }
+        if let Some(backend) = parse_env(env, "APP_DB_BACKEND")? {
+            self.database.backend = Some(backend);
+        }
+        if let Some(url) = env("DATABASE_URL") {
+            self.database.url = Some(url);
+        }
+        if let Some(pool_size) = parse_env(env, "APP_DB_POOL_SIZE")? {
+            self.database.pool_size = pool_size;
+        }
+        if let Some(path) = env("APP_INDEX_PATH") {
+            self.index.path = Some(PathBuf::from(path));
+        }
+        if let Some(writer_memory) = parse_env(env, "APP_WRITER_MEMORY")? {
+            self.index.writer_memory = writer_memory;
+        }
+        if let Some(size) = parse_env(env, "APP_DEFAULT_PAGE_SIZE")? {
+            self.pagination.default_page_size = size;
+        }
+        if let Some(size) = parse_env(env, "APP_MAX_PAGE_SIZE")? {
+            self.pagination.max_page_size = size;
+        }
+        if let Some(window) = parse_env(env, "APP_MAX_RESULT_WINDOW")? {
+            self.pagination.max_result_window = window;
+        }
+        if let Some(paths) = env_paths(env, "APP_DICT_PATHS") {
+            self.tokenizer.dict_paths = paths;
+        }
+        if let Some(roots) = env_paths(env, "APP_CRAWLER_ROOTS") {
+            self.crawler.roots = roots;
+        }
+        if let Some(path) = env("APP_CLASSIFIER_PATH") {
+            self.classifier.path = Some(PathBuf::from(path));
+        }
+        if let Some(allow) = parse_env(env, "APP_AUTH_ALLOW_ANONYMOUS")? {
+            self.auth.allow_anonymous = allow;
+        }
+        if let Some(secret) = env("APP_JWT_SECRET") {
+            self.auth.jwt.get_or_insert_with(Default::default).secret = Some(secret);
+        }
+        if let Some(format) = parse_env(env, "APP_LOG_FORMAT")? {
+            self.log.format = format;
+        }
+        if let Some(filter) = env("RUST_LOG") {
+            self.log.filter = filter;
+        }
+        Ok(())
+    }
+
+    fn apply_cli(&mut self, cli: Cli) {
+        if let Some(bind) = cli.bind {
+            self.server.bind = bind;
+        }
+        if let Some(port) = cli.port {
+            self.server.bind.set_port(port);
+        }
+        if let Some(timeout) = cli.shutdown_timeout {
+            self.server.shutdown_timeout = timeout;
+        }
+        if let Some(backend) = cli.backend {
+            self.database.backend = Some(backend);
+        }
+        if let Some(url) = cli.database_url {
+            self.database.url = Some(url);
+        }
+        if let Some(pool_size) = cli.pool_size {
+            self.database.pool_size = pool_size;
+        }
+        if let Some(path) = cli.index_path {
+            self.index.path = Some(path);
+        }
+        if let Some(writer_memory) = cli.writer_memory {
+            self.index.writer_memory = writer_memory;
+        }
+        if let Some(size) = cli.default_page_size {
+            self.pagination.default_page_size = size;
+        }
+        if let Some(size) = cli.max_page_size {
+            self.pagination.max_page_size = size;
+        }
+        if let Some(window) = cli.max_result_window {
+            self.pagination.max_result_window = window;
+        }
+        if !cli.dict_paths.is_empty() {
+            self.tokenizer.dict_paths = cli.dict_paths;
+        }
+        if !cli.crawler_roots.is_empty() {
+            self.crawler.roots = cli.crawler_roots;
+        }
+        if let Some(format) = cli.log_format {
+            self.log.format = format;
+        }
+        if let Some(filter) = cli.log_filter {
+            self.log.filter = filter;
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.database.backend() == StoreBackend::Postgres
+            && !self.database.url.as_deref().is_some_and(is_postgres_url)
+        {
+            return Err(invalid(
+                "database.url",
+                "the postgres backend needs a postgres:// or postgresql:// url, \
+                 set DATABASE_URL, --database-url or `url` in [database]",
+            ));
+        }
+        if self.database.pool_size == 0 {
+            return Err(invalid("database.pool_size", "must be at least 1"));
+        }
+        if self.index.writer_memory < MIN_WRITER_MEMORY {
+            return Err(invalid(
+                "index.writer_memory",
+                format!("must be at least {} bytes", MIN_WRITER_MEMORY),
+            ));
+        }
+        if let Some(path) = &self.index.path {
+            if path.exists() && !path.is_dir() {
+                return Err(invalid(
+                    "index.path",
+                    format!("{} is not a directory", path.display()),
+                ));
+            }
+        }
+        if self.pagination.default_page_size == 0 {
+            return Err(invalid(
+                "pagination.default_page_size",
+                "must be at least 1",
+            ));
+        }
+        if self.pagination.max_page_size < self.pagination.default_page_size {
+            return Err(invalid(
+                "pagination.max_page_size",
+                "must not be smaller than default_page_size",
+            ));
+        }
+        if self.pagination.max_result_window < self.pagination.max_page_size {
+            return Err(invalid(
+                "pagination.max_result_window",
+                "must not be smaller than max_page_size",
+            ));
+        }
+        if let Err(err) = tracing_subscriber::EnvFilter::try_new(&self.log.filter) {
+            return Err(invalid("log.filter", err.to_string()));
+        }
+        for key in &self.auth.api_keys {
+            if key.key_sha256.len() != 64 || hex::decode(&key.key_sha256).is_err() {
+                return Err(invalid(
+                    "auth.api_keys.key_sha256",
+                    format!("{}: expected a hex encoded SHA-256 digest", key.name),
+                ));
+            }
+            if key.roles.is_empty() {
+                return Err(invalid(
+                    "auth.api_keys.roles",
+                    format!("{}: at least one role is required", key.name),
+                ));
+            }
+        }
+        if let Some(jwt) = &self.auth.jwt {
+            match (&jwt.secret, &jwt.public_key) {
+                (Some(_), Some(_)) | (None, None) => {
+                    return Err(invalid(
+                        "auth.jwt",
+                        "set exactly one of `secret` (HS256) or `public_key` (RS256)",
+                    ))
+                }
+                (None, Some(path)) if !path.is_file() => {
+                    return Err(invalid(
+                        "auth.jwt.public_key",
+                        format!("{} does not exist", path.display()),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        for (key, quota) in [
+            ("limits.per_ip", &self.limits.per_ip),
+            ("limits.per_key", &self.limits.per_key),
+        ] {
+            if !(quota.rate >= 0.0 && quota.rate.is_finite()) {
+                return Err(invalid(key, "rate must be a non-negative number"));
+            }
+            if quota.enabled() && quota.burst == 0 {
+                return Err(invalid(key, "burst must be at least 1"));
+            }
+        }
+        if self.limits.body_bytes == 0 {
+            return Err(invalid("limits.body_bytes", "must be at least 1"));
+        }
+        if self.limits.bulk_body_bytes < self.limits.body_bytes {
+            return Err(invalid(
+                "limits.bulk_body_bytes",
+                "must not be smaller than body_bytes",
+            ));
+        }
+        if self.limits.max_query_len == 0 {
+            return Err(invalid("limits.max_query_len", "must be at least 1"));
+        }
+        if self.limits.max_query_terms == 0 {
+            return Err(invalid("limits.max_query_terms", "must be at least 1"));
+        }
+        let FieldBoosts {
+            title,
+            body,
+            url,
+            pinyin,
+        } = self.search.boosts;
+        if [title, body, url, pinyin]
+            .iter()
+            .any(|boost| !(boost.is_finite() && *boost >= 0.0))
+        {
+            return Err(invalid(
+                "search.boosts",
+                "boosts must be non-negative numbers",
+            ));
+        }
+        if !(self.search.bm25.k1.is_finite() && self.search.bm25.k1 >= 0.0) {
+            return Err(invalid("search.bm25.k1", "must be a non-negative number"));
+        }
+        if !(0.0..=1.0).contains(&self.search.bm25.b) {
+            return Err(invalid("search.bm25.b", "must be between 0 and 1"));
+        }
+        for path in &self.tokenizer.dict_paths {
+            if !path.is_file() {
+                return Err(invalid(
+                    "tokenizer.dict_paths",
+                    format!("{} does not exist", path.display()),
+                ));
+            }
+        }
+        if self.dedup.threshold >= simhash::BANDS {
+            return Err(invalid(
+                "dedup.threshold",
+                format!(
+                    "must be between 0 and {}: near-duplicates are looked up by {} bands of {} bits, \
+                     and fingerprints that differ in {} or more bits may share no band",
+                    simhash::BANDS - 1,
+                    simhash::BANDS,
+                    64 / simhash::BANDS,
+                    simhash::BANDS
+                ),
+            ));
+        }
+        if let Some(path) = &self.classifier.path {
+            if !path.is_file() {
+                return Err(invalid(
+                    "classifier.path",
+                    format!("{} does not exist", path.display()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.server.bind
+    }
+}
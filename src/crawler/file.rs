@@ -10,8 +10,22 @@ fn read_text_file(file_path: &Path) -> io::Result<String> {
     Ok(content)
 }
 
-pub fn read_file_data<'a>() -> Vec<(String, String)> {
-    let folder_path = Path::new("./data");
+// 语料的一行：id_!_分类代码_!_分类_!_标题_!_关键词，不足五列时没有分类
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusLine {
+    pub category: Option<String>,
+    pub title: String,
+    pub keywords: String,
+}
+
+pub fn read_file_data(folder_path: &Path) -> Vec<(String, String)> {
+    read_corpus(folder_path)
+        .into_iter()
+        .map(|line| (line.title, line.keywords))
+        .collect()
+}
+
+pub fn read_corpus(folder_path: &Path) -> Vec<CorpusLine> {
     let mut res = Vec::new();
 
     for entry in WalkDir::new(folder_path).into_iter().filter_map(|e| e.ok()) {
@@ -19,25 +33,27 @@ pub fn read_file_data<'a>() -> Vec<(String, String)> {
             let file_path = entry.path();
             if let Some(extension) = file_path.extension() {
                 if extension == "txt" {
-                    match read_text_file(&file_path) {
+                    match read_text_file(file_path) {
                         Ok(content) => {
-                            println!("Content of {}:", file_path.display());
+                            tracing::debug!("reading {}", file_path.display());
 
                             let contents: Vec<&str> = content.split("\n").collect();
 
                             // 输出每个部分
                             for item in contents {
                                 let rawabs: Vec<&str> = item.split("_!_").collect();
-                                if rawabs.len() > 2 {
-                                    res.push((
-                                        rawabs[rawabs.len() - 2].to_owned(),
-                                        rawabs[rawabs.len() - 1].to_owned(),
-                                    ));
+                                let len = rawabs.len();
+                                if len > 2 {
+                                    res.push(CorpusLine {
+                                        category: (len > 4).then(|| rawabs[len - 3].to_owned()),
+                                        title: rawabs[len - 2].to_owned(),
+                                        keywords: rawabs[len - 1].to_owned(),
+                                    });
                                 }
                             }
                         }
                         Err(err) => {
-                            eprintln!("Error reading {}: {}", file_path.display(), err);
+                            tracing::warn!("error reading {}: {}", file_path.display(), err);
                         }
                     }
                 }
@@ -1,11 +1,27 @@
 // 导入子模块
 mod file;
 
-// 在模块中定义函数
-pub fn read_file_data() -> Vec<(String, String)> {
-    println!("Hello from read_file_data!");
+pub use file::CorpusLine;
+
+use std::path::{Path, PathBuf};
+
+// 读取配置的多个语料目录
+pub fn read_file_data_from(roots: &[PathBuf]) -> Vec<(String, String)> {
+    tracing::info!(?roots, "reading corpus files");
 
     // 调用子模块的函数
-    let read_file_data = file::read_file_data();
-    read_file_data
+    roots
+        .iter()
+        .flat_map(|root| file::read_file_data(Path::new(root)))
+        .collect()
+}
+
+// 和 read_file_data_from 相同，但保留每行的分类，用于训练分类器
+pub fn read_corpus_from(roots: &[PathBuf]) -> Vec<CorpusLine> {
+    tracing::info!(?roots, "reading labelled corpus files");
+
+    roots
+        .iter()
+        .flat_map(|root| file::read_corpus(Path::new(root)))
+        .collect()
 }
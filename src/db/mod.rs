@@ -0,0 +1,29 @@
+// 数据访问层，基于 diesel 生成的 schema.rs
+pub mod models;
+pub mod postgres;
+mod queries;
+pub mod sqlite;
+pub mod store;
+
+use crate::config::{DatabaseConfig, StoreBackend};
+use crate::Result;
+use std::sync::Arc;
+
+pub use models::{Doc, NewDoc, UpdateDoc};
+pub use postgres::{PgPool, PgStore};
+pub use sqlite::SqliteStore;
+pub use store::{DocumentStore, ListQuery, Order, Page, PoolStatus};
+
+// 根据配置选择存储后端，并执行迁移
+pub async fn connect(config: &DatabaseConfig) -> Result<Arc<dyn DocumentStore>> {
+    let store: Arc<dyn DocumentStore> = match config.backend() {
+        StoreBackend::Postgres => {
+            let url = config.url.as_deref().unwrap_or_default();
+            Arc::new(PgStore::connect(url, config.pool_size).await?)
+        }
+        StoreBackend::Sqlite => {
+            Arc::new(SqliteStore::connect(&config.sqlite_path(), config.pool_size).await?)
+        }
+    };
+    Ok(store)
+}
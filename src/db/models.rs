@@ -0,0 +1,64 @@
+use crate::schema::docs;
+use diesel::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Selectable, Queryable)]
+#[diesel(table_name = docs)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Doc {
+    pub id: i32,
+    pub title: String,
+    pub url: String,
+    pub content: String,
+    pub doc_type: String,
+    pub published: bool,
+    // 标题和正文的 SimHash 指纹，以有符号数保存
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simhash: Option<i64>,
+    // 写入时检测到的近似重复文档
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Insertable)]
+#[diesel(table_name = docs)]
+pub struct NewDoc {
+    pub title: String,
+    pub url: String,
+    pub content: String,
+    // 为空时由分类模型自动填写
+    #[serde(default)]
+    pub doc_type: String,
+    // 为空时使用数据库默认值 false
+    pub published: Option<bool>,
+    // 写入时由服务端计算
+    #[serde(skip)]
+    pub simhash: Option<i64>,
+    #[serde(skip)]
+    pub duplicate_of: Option<i32>,
+}
+
+// 只更新传入的字段
+#[derive(Debug, Clone, Default, Deserialize, AsChangeset)]
+#[diesel(table_name = docs)]
+pub struct UpdateDoc {
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub content: Option<String>,
+    pub doc_type: Option<String>,
+    pub published: Option<bool>,
+    // 标题或正文变化时重新计算
+    #[serde(skip)]
+    pub simhash: Option<i64>,
+}
+
+impl UpdateDoc {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.url.is_none()
+            && self.content.is_none()
+            && self.doc_type.is_none()
+            && self.published.is_none()
+            && self.simhash.is_none()
+    }
+}
@@ -0,0 +1,114 @@
+use super::models::{Doc, NewDoc, UpdateDoc};
+use super::store::{DocumentStore, ListQuery, Page, PoolStatus, DEFAULT_LIMIT};
+use crate::error::AppError;
+use crate::Result;
+use async_trait::async_trait;
+use deadpool_diesel::postgres::{Manager, Pool};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/");
+
+pub type PgPool = Pool;
+
+mod queries {
+    crate::db::queries::doc_queries!(diesel::PgConnection);
+}
+
+pub fn pool(db_url: &str, max_size: usize) -> Result<PgPool> {
+    let manager = Manager::new(db_url, deadpool_diesel::Runtime::Tokio1);
+    Pool::builder(manager)
+        .max_size(max_size)
+        .build()
+        .map_err(|err| AppError::from_db_err(Box::new(err)))
+}
+
+pub async fn run_migrations(pool: &PgPool) -> Result<()> {
+    let conn = pool.get().await?;
+    conn.interact(|conn| conn.run_pending_migrations(MIGRATIONS).map(|_| ()))
+        .await?
+        .map_err(AppError::from_db_err)?;
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // 创建连接池并执行迁移
+    pub async fn connect(db_url: &str, max_size: usize) -> Result<Self> {
+        let pool = pool(db_url, max_size)?;
+        run_migrations(&pool).await?;
+        Ok(Self::new(pool))
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl DocumentStore for PgStore {
+    #[tracing::instrument(name = "pg.create", skip_all)]
+    async fn create(&self, doc: NewDoc) -> Result<Doc> {
+        let conn = self.pool.get().await?;
+        Ok(conn.interact(|conn| queries::create(conn, doc)).await??)
+    }
+
+    #[tracing::instrument(name = "pg.get", skip(self))]
+    async fn get(&self, id: i32) -> Result<Option<Doc>> {
+        let conn = self.pool.get().await?;
+        Ok(conn.interact(move |conn| queries::get(conn, id)).await??)
+    }
+
+    #[tracing::instrument(name = "pg.list", skip(self))]
+    async fn list(&self, query: ListQuery) -> Result<Page<Doc>> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let conn = self.pool.get().await?;
+        let rows = conn
+            .interact(move |conn| queries::list(conn, query, limit))
+            .await??;
+        Ok(Page::from_rows(rows, limit))
+    }
+
+    #[tracing::instrument(name = "pg.update", skip(self, changes))]
+    async fn update(&self, id: i32, changes: UpdateDoc) -> Result<Option<Doc>> {
+        let conn = self.pool.get().await?;
+        Ok(conn
+            .interact(move |conn| queries::update(conn, id, changes))
+            .await??)
+    }
+
+    #[tracing::instrument(name = "pg.delete", skip(self))]
+    async fn delete(&self, id: i32) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        Ok(conn
+            .interact(move |conn| queries::delete(conn, id))
+            .await??)
+    }
+
+    async fn check(&self) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let pending = conn
+            .interact(|conn| conn.has_pending_migration(MIGRATIONS))
+            .await?
+            .map_err(AppError::from_db_err)?;
+        if pending {
+            return Err(AppError::db_msg("migrations are not applied"));
+        }
+        Ok(())
+    }
+
+    fn pool_status(&self) -> PoolStatus {
+        self.pool.status().into()
+    }
+
+    fn close(&self) {
+        self.pool.close();
+    }
+}
@@ -0,0 +1,69 @@
+// 两种后端的 diesel 查询完全相同，只是连接类型不同，用宏为每种连接各生成一份
+macro_rules! doc_queries {
+    ($conn:ty) => {
+        use crate::db::models::{Doc, NewDoc, UpdateDoc};
+        use crate::db::store::{ListQuery, Order};
+        use crate::schema::docs;
+        use diesel::prelude::*;
+
+        pub fn create(conn: &mut $conn, doc: NewDoc) -> QueryResult<Doc> {
+            diesel::insert_into(docs::table)
+                .values(doc)
+                .returning(Doc::as_returning())
+                .get_result(conn)
+        }
+
+        pub fn get(conn: &mut $conn, id: i32) -> QueryResult<Option<Doc>> {
+            docs::table
+                .find(id)
+                .select(Doc::as_select())
+                .first(conn)
+                .optional()
+        }
+
+        // 多取一条，用来判断是否还有下一页
+        pub fn list(conn: &mut $conn, query: ListQuery, limit: i64) -> QueryResult<Vec<Doc>> {
+            let mut q = docs::table.select(Doc::as_select()).into_boxed();
+            if let Some(doc_type) = query.doc_type {
+                q = q.filter(docs::doc_type.eq(doc_type));
+            }
+            if let Some(published) = query.published {
+                q = q.filter(docs::published.eq(published));
+            }
+            q = match query.order {
+                Order::Asc => {
+                    if let Some(after_id) = query.after_id {
+                        q = q.filter(docs::id.gt(after_id));
+                    }
+                    q.order(docs::id.asc())
+                }
+                Order::Desc => {
+                    if let Some(after_id) = query.after_id {
+                        q = q.filter(docs::id.lt(after_id));
+                    }
+                    q.order(docs::id.desc())
+                }
+            };
+            q.limit(limit + 1).load(conn)
+        }
+
+        pub fn update(conn: &mut $conn, id: i32, changes: UpdateDoc) -> QueryResult<Option<Doc>> {
+            // diesel 不允许空的 changeset，没有字段要改时直接返回原数据
+            if changes.is_empty() {
+                return get(conn, id);
+            }
+            diesel::update(docs::table.find(id))
+                .set(changes)
+                .returning(Doc::as_returning())
+                .get_result(conn)
+                .optional()
+        }
+
+        pub fn delete(conn: &mut $conn, id: i32) -> QueryResult<bool> {
+            let deleted = diesel::delete(docs::table.find(id)).execute(conn)?;
+            Ok(deleted > 0)
+        }
+    };
+}
+
+pub(crate) use doc_queries;
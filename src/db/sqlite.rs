@@ -0,0 +1,125 @@
+use super::models::{Doc, NewDoc, UpdateDoc};
+use super::store::{DocumentStore, ListQuery, Page, PoolStatus, DEFAULT_LIMIT};
+use crate::error::AppError;
+use crate::Result;
+use async_trait::async_trait;
+use deadpool::managed::{HookError, HookErrorCause};
+use deadpool_diesel::sqlite::{Hook, Manager, Pool};
+use diesel::connection::SimpleConnection;
+use diesel::SqliteConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations_sqlite/");
+
+pub type SqlitePool = Pool;
+
+mod queries {
+    crate::db::queries::doc_queries!(diesel::SqliteConnection);
+}
+
+// 内嵌的 SQLite 存储，不需要数据库服务，适合单机部署、本地开发和 CI
+#[derive(Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    // path 为数据库文件路径，":memory:" 为内存数据库
+    pub async fn connect(path: &str, max_size: usize) -> Result<Self> {
+        // 每个内存数据库连接都是独立的库，只能用一个连接
+        let max_size = if path == ":memory:" { 1 } else { max_size };
+        let manager = Manager::new(path, deadpool_diesel::Runtime::Tokio1);
+        let pool = Pool::builder(manager)
+            .max_size(max_size)
+            // 多个连接同时写入时等待锁，而不是直接返回 SQLITE_BUSY；每个连接创建时设置一次
+            .post_create(Hook::async_fn(|conn, _| {
+                Box::pin(async move {
+                    let abort = |err: String| HookError::Abort(HookErrorCause::Message(err));
+                    conn.interact(|conn| conn.batch_execute("PRAGMA busy_timeout = 5000;"))
+                        .await
+                        .map_err(|err| abort(err.to_string()))?
+                        .map_err(|err| abort(err.to_string()))
+                })
+            }))
+            .build()
+            .map_err(|err| AppError::from_db_err(Box::new(err)))?;
+        let store = Self { pool };
+        store
+            .interact(|conn| {
+                conn.batch_execute("PRAGMA journal_mode = WAL;")?;
+                conn.run_pending_migrations(MIGRATIONS)
+                    .map(|_| ())
+                    .map_err(AppError::from_db_err)
+            })
+            .await?;
+        Ok(store)
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn interact<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut SqliteConnection) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let conn = self.pool.get().await?;
+        conn.interact(f).await?
+    }
+}
+
+#[async_trait]
+impl DocumentStore for SqliteStore {
+    #[tracing::instrument(name = "sqlite.create", skip_all)]
+    async fn create(&self, doc: NewDoc) -> Result<Doc> {
+        self.interact(|conn| Ok(queries::create(conn, doc)?)).await
+    }
+
+    #[tracing::instrument(name = "sqlite.get", skip(self))]
+    async fn get(&self, id: i32) -> Result<Option<Doc>> {
+        self.interact(move |conn| Ok(queries::get(conn, id)?)).await
+    }
+
+    #[tracing::instrument(name = "sqlite.list", skip(self))]
+    async fn list(&self, query: ListQuery) -> Result<Page<Doc>> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let rows = self
+            .interact(move |conn| Ok(queries::list(conn, query, limit)?))
+            .await?;
+        Ok(Page::from_rows(rows, limit))
+    }
+
+    #[tracing::instrument(name = "sqlite.update", skip(self, changes))]
+    async fn update(&self, id: i32, changes: UpdateDoc) -> Result<Option<Doc>> {
+        self.interact(move |conn| Ok(queries::update(conn, id, changes)?))
+            .await
+    }
+
+    #[tracing::instrument(name = "sqlite.delete", skip(self))]
+    async fn delete(&self, id: i32) -> Result<bool> {
+        self.interact(move |conn| Ok(queries::delete(conn, id)?))
+            .await
+    }
+
+    async fn check(&self) -> Result<()> {
+        let pending = self
+            .interact(|conn| {
+                conn.has_pending_migration(MIGRATIONS)
+                    .map_err(AppError::from_db_err)
+            })
+            .await?;
+        if pending {
+            return Err(AppError::db_msg("migrations are not applied"));
+        }
+        Ok(())
+    }
+
+    fn pool_status(&self) -> PoolStatus {
+        self.pool.status().into()
+    }
+
+    fn close(&self) {
+        self.pool.close();
+    }
+}
@@ -0,0 +1,79 @@
+use super::models::{Doc, NewDoc, UpdateDoc};
+use crate::Result;
+use async_trait::async_trait;
+use serde_derive::{Deserialize, Serialize};
+
+pub const DEFAULT_LIMIT: i64 = 20;
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ListQuery {
+    pub after_id: Option<i32>,
+    pub limit: Option<i64>,
+    pub doc_type: Option<String>,
+    pub published: Option<bool>,
+    #[serde(default)]
+    pub order: Order,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub docs: Vec<T>,
+    // id to pass as `after_id` for the next page, `None` on the last page
+    pub next_cursor: Option<i32>,
+}
+
+impl Page<Doc> {
+    // rows 比 limit 多取一条，用来判断是否还有下一页
+    pub(crate) fn from_rows(mut rows: Vec<Doc>, limit: i64) -> Self {
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|doc| doc.id)
+        } else {
+            None
+        };
+        Page {
+            docs: rows,
+            next_cursor,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PoolStatus {
+    pub max_size: usize,
+    pub size: usize,
+    pub available: isize,
+}
+
+impl From<deadpool_diesel::Status> for PoolStatus {
+    fn from(status: deadpool_diesel::Status) -> Self {
+        Self {
+            max_size: status.max_size,
+            size: status.size,
+            available: status.available,
+        }
+    }
+}
+
+// 文档存储，Postgres 和内嵌的 SQLite 各有一个实现
+#[async_trait]
+pub trait DocumentStore: Send + Sync {
+    async fn create(&self, doc: NewDoc) -> Result<Doc>;
+    async fn get(&self, id: i32) -> Result<Option<Doc>>;
+    async fn list(&self, query: ListQuery) -> Result<Page<Doc>>;
+    async fn update(&self, id: i32, changes: UpdateDoc) -> Result<Option<Doc>>;
+    async fn delete(&self, id: i32) -> Result<bool>;
+    // 取一个连接并确认迁移都已执行，用于 /readyz
+    async fn check(&self) -> Result<()>;
+    fn pool_status(&self) -> PoolStatus;
+    // 关闭连接池，之后的操作都会失败
+    fn close(&self);
+}
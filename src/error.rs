@@ -1,31 +1,70 @@
+use crate::config::ConfigError;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use axum::Json;
 use deadpool_diesel::{InteractError, PoolError};
+use tantivy::directory::error::OpenDirectoryError;
 use tantivy::{query::QueryParserError, TantivyError};
 #[derive(Debug)]
 pub struct AppError {
     pub message: Option<String>,
-    pub cause: Option<Box<dyn std::error::Error>>,
+    pub cause: Option<Box<dyn std::error::Error + Send>>,
     pub types: AppErrorType,
+    // 附加在 JSON 错误响应中的结构化信息，例如查询语法错误的位置
+    pub details: Option<serde_json::Value>,
 }
 
 impl AppError {
     fn new(
         message: Option<String>,
-        cause: Option<Box<dyn std::error::Error>>,
+        cause: Option<Box<dyn std::error::Error + Send>>,
         types: AppErrorType,
     ) -> Self {
         Self {
             message,
             cause,
             types,
+            details: None,
         }
     }
-    fn from_err(cause: Box<dyn std::error::Error>, types: AppErrorType) -> Self {
+    fn from_err(cause: Box<dyn std::error::Error + Send>, types: AppErrorType) -> Self {
         Self::new(None, Some(cause), types)
     }
     // fn from_str(msg: &str, types: AppErrorType) -> Self {
     //     Self::new(Some(msg.to_string()), None, types)
     // }
+    pub fn from_db_err(cause: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Self::from_err(cause, AppErrorType::Db)
+    }
+    pub fn db_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::Db)
+    }
+    pub fn unauthorized_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::Unauthorized)
+    }
+    pub fn forbidden_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::Forbidden)
+    }
+    pub fn bad_request_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::BadRequest)
+    }
+    pub fn too_many_requests_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::TooManyRequests)
+    }
+    pub fn payload_too_large_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::PayloadTooLarge)
+    }
+    pub fn conflict_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::Conflict)
+    }
+    pub fn unavailable_msg(msg: &str) -> Self {
+        Self::new(Some(msg.to_string()), None, AppErrorType::Unavailable)
+    }
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
     pub fn notfound_opt(message: Option<String>) -> Self {
         Self::new(message, None, AppErrorType::Notfound)
     }
@@ -37,13 +76,28 @@ impl AppError {
     }
 }
 
+// 打印 message，有 cause 时接在后面；message 本身就是 cause 的描述时不重复。完整结构用 {:?}
 impl std::fmt::Display for AppError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        let cause = self.cause.as_ref().map(|cause| cause.to_string());
+        match (&self.message, cause) {
+            (Some(message), Some(cause)) if *message != cause => {
+                write!(f, "{}: {}", message, cause)
+            }
+            (Some(message), _) => write!(f, "{}", message),
+            (None, Some(cause)) => write!(f, "{}", cause),
+            (None, None) => write!(f, "{} error", self.types.code()),
+        }
     }
 }
 
-impl std::error::Error for AppError {}
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause
+            .as_ref()
+            .map(|cause| cause.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
 
 impl From<TantivyError> for AppError {
     fn from(err: TantivyError) -> Self {
@@ -51,12 +105,45 @@ impl From<TantivyError> for AppError {
     }
 }
 
+// 查询解析失败是用户输入的问题
 impl From<QueryParserError> for AppError {
     fn from(err: QueryParserError) -> Self {
+        Self::new(
+            Some(err.to_string()),
+            Some(Box::new(err)),
+            AppErrorType::BadRequest,
+        )
+    }
+}
+
+impl From<OpenDirectoryError> for AppError {
+    fn from(err: OpenDirectoryError) -> Self {
+        Self::from_err(Box::new(err), AppErrorType::Engine)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
         Self::from_err(Box::new(err), AppErrorType::Engine)
     }
 }
 
+impl From<ConfigError> for AppError {
+    fn from(err: ConfigError) -> Self {
+        Self::new(
+            Some(err.to_string()),
+            Some(Box::new(err)),
+            AppErrorType::Server,
+        )
+    }
+}
+
+impl From<hyper::Error> for AppError {
+    fn from(err: hyper::Error) -> Self {
+        Self::from_err(Box::new(err), AppErrorType::Server)
+    }
+}
+
 impl From<PoolError> for AppError {
     fn from(err: PoolError) -> Self {
         Self::from_err(Box::new(err), AppErrorType::Db)
@@ -75,13 +162,68 @@ impl From<InteractError> for AppError {
     }
 }
 
+// spawn_blocking 中的任务 panic 或被取消
+impl From<tokio::task::JoinError> for AppError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Self::from_err(Box::new(err), AppErrorType::Server)
+    }
+}
+
+// 请求体不是合法 JSON 或超过大小限制
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        let types = match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => AppErrorType::PayloadTooLarge,
+            _ => AppErrorType::BadRequest,
+        };
+        Self::new(Some(rejection.body_text()), None, types)
+    }
+}
+
+// 路径参数类型不对，例如 /docs/abc
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        let types = match rejection.status() {
+            status if status.is_server_error() => AppErrorType::Server,
+            _ => AppErrorType::BadRequest,
+        };
+        Self::new(Some(rejection.body_text()), None, types)
+    }
+}
+
+// 查询字符串缺少参数或类型不对
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(Some(rejection.body_text()), None, AppErrorType::BadRequest)
+    }
+}
+
+// 错误统一返回 JSON：{"error": "not_found", "message": "..."}
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        // 响应里不带 cause，服务端错误在这里记下来
+        let status = self.types.status_code();
+        if status.is_server_error() {
+            tracing::error!(
+                error = ?self.cause,
+                kind = ?self.types,
+                message = ?self.message,
+                "request failed with {}",
+                status
+            );
+        }
         let msg = match self.message {
             Some(msg) => msg.clone(),
             None => "有错误发生".to_string(),
         };
-        msg.into_response()
+        let mut body = serde_json::json!({
+            "error": self.types.code(),
+            "message": msg,
+        });
+        if let Some(details) = self.details {
+            body["details"] = details;
+        }
+        (status, Json(body)).into_response()
     }
 }
 
@@ -91,4 +233,50 @@ pub enum AppErrorType {
     Engine,
     Template,
     Notfound,
+    Server,
+    // 没有凭证或凭证无效
+    Unauthorized,
+    // 凭证有效但角色不够
+    Forbidden,
+    // 请求参数不合法，例如查询过长
+    BadRequest,
+    TooManyRequests,
+    PayloadTooLarge,
+    // 和已有数据冲突，例如写入近似重复的文档
+    Conflict,
+    // 功能依赖的资源没有配置，例如分类模型
+    Unavailable,
+}
+
+impl AppErrorType {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppErrorType::Notfound => StatusCode::NOT_FOUND,
+            AppErrorType::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppErrorType::Forbidden => StatusCode::FORBIDDEN,
+            AppErrorType::BadRequest => StatusCode::BAD_REQUEST,
+            AppErrorType::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            AppErrorType::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AppErrorType::Conflict => StatusCode::CONFLICT,
+            AppErrorType::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppErrorType::Db => "db",
+            AppErrorType::Engine => "engine",
+            AppErrorType::Template => "template",
+            AppErrorType::Notfound => "not_found",
+            AppErrorType::Server => "server",
+            AppErrorType::Unauthorized => "unauthorized",
+            AppErrorType::Forbidden => "forbidden",
+            AppErrorType::BadRequest => "bad_request",
+            AppErrorType::TooManyRequests => "too_many_requests",
+            AppErrorType::PayloadTooLarge => "payload_too_large",
+            AppErrorType::Conflict => "conflict",
+            AppErrorType::Unavailable => "unavailable",
+        }
+    }
 }
@@ -1,5 +1,13 @@
+pub mod auth;
+pub mod config;
 pub mod crawler;
+pub mod db;
 pub mod error;
+pub mod logging;
+pub mod metrics;
 pub mod nlpcut;
+pub mod ratelimit;
+pub mod schema;
 pub mod search;
+pub mod server;
 pub type Result<T> = std::result::Result<T, error::AppError>;
@@ -0,0 +1,80 @@
+// Prometheus 指标，通过 GET /metrics 导出
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use prometheus::{
+    register_gauge_vec, register_histogram, register_histogram_vec, register_int_gauge, Encoder,
+    GaugeVec, Histogram, HistogramVec, IntGauge, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+pub static HTTP_REQUEST_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "http_request_duration_seconds",
+        "HTTP request latency by route",
+        &["method", "route", "status"]
+    )
+    .unwrap()
+});
+
+pub static SEARCH_HITS: LazyLock<Histogram> = LazyLock::new(|| {
+    register_histogram!(
+        "search_hits",
+        "Number of matching documents per search",
+        vec![0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 1000.0, 10000.0]
+    )
+    .unwrap()
+});
+
+pub static INDEX_COMMIT_SECONDS: LazyLock<Histogram> = LazyLock::new(|| {
+    register_histogram!(
+        "index_commit_duration_seconds",
+        "Duration of index writer commits"
+    )
+    .unwrap()
+});
+
+pub static INDEX_DOCS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("index_docs", "Number of documents in the index").unwrap()
+});
+
+pub static INDEX_SEGMENTS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("index_segments", "Number of segments in the index").unwrap()
+});
+
+pub static DB_POOL: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "db_pool_connections",
+        "Document store connection pool, by state (max, size, available)",
+        &["state"]
+    )
+    .unwrap()
+});
+
+// 按路由模板（例如 /docs/:id）统计请求耗时，避免每个 id 一个时间序列
+pub async fn track_requests<B>(req: Request<B>, next: Next<B>) -> Response {
+    let start = Instant::now();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = req.method().to_string();
+
+    let response = next.run(req).await;
+
+    HTTP_REQUEST_SECONDS
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .observe(start.elapsed().as_secs_f64());
+    response
+}
+
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .unwrap_or_default();
+    String::from_utf8(buffer).unwrap_or_default()
+}
@@ -0,0 +1,148 @@
+// 朴素贝叶斯文本分类：jieba 切词去掉停用词后按多项式模型统计词频，拉普拉斯平滑。
+// 用 `_!_` 语料的分类标签离线训练（cargo run --release --example train_classifier），
+// 保存成 JSON，服务启动时按配置加载
+use super::stopwords::STOPWORDS_CMN;
+use jieba_rs::Jieba;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct ClassStats {
+    docs: usize,
+    words: usize,
+    counts: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Classifier {
+    classes: BTreeMap<String, ClassStats>,
+    // 所有分类合起来的词表大小
+    vocabulary: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Prediction {
+    pub label: String,
+    pub probability: f64,
+}
+
+// 含有字母或数字、不是停用词的词，英文转小写；SimHash 也使用同样的特征
+pub(crate) fn features(jieba: &Jieba, text: &str) -> Vec<String> {
+    jieba
+        .cut(text, true)
+        .into_iter()
+        .filter(|word| word.chars().any(char::is_alphanumeric) && !STOPWORDS_CMN.contains(word))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl Classifier {
+    // samples 是 (分类, 文本)；总出现次数少于 min_count 的词不进入词表
+    pub fn train<L, T>(
+        jieba: &Jieba,
+        samples: impl IntoIterator<Item = (L, T)>,
+        min_count: usize,
+    ) -> Self
+    where
+        L: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let mut classes: BTreeMap<String, ClassStats> = BTreeMap::new();
+        let mut totals: HashMap<String, usize> = HashMap::new();
+        for (label, text) in samples {
+            let stats = classes.entry(label.as_ref().to_string()).or_default();
+            stats.docs += 1;
+            for word in features(jieba, text.as_ref()) {
+                *totals.entry(word.clone()).or_default() += 1;
+                *stats.counts.entry(word).or_default() += 1;
+            }
+        }
+        for stats in classes.values_mut() {
+            stats.counts.retain(|word, _| totals[word] >= min_count);
+            stats.words = stats.counts.values().sum();
+        }
+        let vocabulary = totals.values().filter(|&&count| count >= min_count).count();
+        Self {
+            classes,
+            vocabulary,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        serde_json::from_reader(reader).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self).map_err(io::Error::from)
+    }
+
+    pub fn labels(&self) -> Vec<&str> {
+        self.classes.keys().map(String::as_str).collect()
+    }
+
+    // 按概率从高到低返回所有分类；文本中没有词表里的词时返回空
+    pub fn classify(&self, jieba: &Jieba, text: &str) -> Vec<Prediction> {
+        let words: Vec<String> = features(jieba, text)
+            .into_iter()
+            .filter(|word| {
+                self.classes
+                    .values()
+                    .any(|stats| stats.counts.contains_key(word))
+            })
+            .collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let docs: usize = self.classes.values().map(|stats| stats.docs).sum();
+        let scores: Vec<(&String, f64)> = self
+            .classes
+            .iter()
+            .map(|(label, stats)| {
+                let denominator = (stats.words + self.vocabulary) as f64;
+                let likelihood: f64 = words
+                    .iter()
+                    .map(|word| {
+                        let count = stats.counts.get(word).copied().unwrap_or_default();
+                        ((count + 1) as f64 / denominator).ln()
+                    })
+                    .sum();
+                (label, (stats.docs as f64 / docs as f64).ln() + likelihood)
+            })
+            .collect();
+        // 对数概率做 softmax 得到归一化的概率
+        let max = scores
+            .iter()
+            .map(|(_, score)| *score)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let sum: f64 = scores.iter().map(|(_, score)| (score - max).exp()).sum();
+        let mut predictions: Vec<Prediction> = scores
+            .into_iter()
+            .map(|(label, score)| Prediction {
+                label: label.clone(),
+                probability: (score - max).exp() / sum,
+            })
+            .collect();
+        predictions.sort_by(|a, b| {
+            b.probability
+                .partial_cmp(&a.probability)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.label.cmp(&b.label))
+        });
+        predictions
+    }
+
+    pub fn predict(&self, jieba: &Jieba, text: &str) -> Option<Prediction> {
+        self.classify(jieba, text).into_iter().next()
+    }
+}
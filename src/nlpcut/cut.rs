@@ -0,0 +1,91 @@
+// 对外提供和索引相同的切词：jieba 的精确模式（可关闭 HMM）、全模式和搜索引擎模式，
+// 精确模式下可以附带词性，可选地去掉停用词
+use super::stopwords::STOPWORDS_CMN;
+use jieba_rs::Jieba;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutMode {
+    // Jieba::cut
+    #[default]
+    Cut,
+    // Jieba::cut_all，列出所有可能的词
+    CutAll,
+    // Jieba::cut_for_search，长词再切出短词，和索引使用的切分方式一致
+    CutForSearch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CutOptions {
+    pub mode: CutMode,
+    // 用 HMM 识别未登录词，cut_all 模式不使用
+    pub hmm: bool,
+    // 附带词性，只支持 cut 模式
+    pub pos: bool,
+    // 去掉停用词和空白
+    pub remove_stopwords: bool,
+}
+
+impl Default for CutOptions {
+    fn default() -> Self {
+        Self {
+            mode: CutMode::Cut,
+            hmm: true,
+            pos: false,
+            remove_stopwords: false,
+        }
+    }
+}
+
+impl CutOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pos && self.mode != CutMode::Cut {
+            return Err("pos tagging is only supported in `cut` mode".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Word {
+    pub word: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+fn is_stopword(word: &str) -> bool {
+    word.trim().is_empty() || STOPWORDS_CMN.contains(&word)
+}
+
+pub fn cut(jieba: &Jieba, text: &str, options: &CutOptions) -> Result<Vec<Word>, String> {
+    options.validate()?;
+    let words: Vec<Word> = if options.pos {
+        jieba
+            .tag(text, options.hmm)
+            .into_iter()
+            .map(|tag| Word {
+                word: tag.word.to_string(),
+                tag: Some(tag.tag.to_string()),
+            })
+            .collect()
+    } else {
+        let words = match options.mode {
+            CutMode::Cut => jieba.cut(text, options.hmm),
+            CutMode::CutAll => jieba.cut_all(text),
+            CutMode::CutForSearch => jieba.cut_for_search(text, options.hmm),
+        };
+        words
+            .into_iter()
+            .map(|word| Word {
+                word: word.to_string(),
+                tag: None,
+            })
+            .collect()
+    };
+    Ok(words
+        .into_iter()
+        .filter(|word| !options.remove_stopwords || !is_stopword(&word.word))
+        .collect())
+}
@@ -0,0 +1,121 @@
+// 关键词提取：先用 candidates 切词过滤，再用 TF-IDF 或 TextRank 排序。
+// TF-IDF 的 IDF 由调用方提供（SearchService 从索引的文档频率计算），
+// TextRank 只依赖文本本身，按窗口内的共现关系迭代计算词的重要度
+use super::stopwords::STOPWORDS_CMN;
+use jieba_rs::Jieba;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// TextRank 共现窗口大小、阻尼系数和迭代次数
+const WINDOW: usize = 5;
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keyword {
+    pub word: String,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordMethod {
+    #[default]
+    TfIdf,
+    TextRank,
+}
+
+// 至少两个字、不是纯数字、不是停用词
+fn is_candidate(word: &str) -> bool {
+    word.chars().count() >= 2
+        && word.chars().all(char::is_alphanumeric)
+        && !word.chars().all(|c| c.is_ascii_digit())
+        && !STOPWORDS_CMN.contains(&word)
+}
+
+pub fn candidates<'a>(jieba: &Jieba, text: &'a str) -> Vec<&'a str> {
+    jieba
+        .cut(text, true)
+        .into_iter()
+        .filter(|word| is_candidate(word))
+        .collect()
+}
+
+fn top(weights: HashMap<&str, f64>, top_k: usize) -> Vec<Keyword> {
+    let mut keywords: Vec<Keyword> = weights
+        .into_iter()
+        .map(|(word, weight)| Keyword {
+            word: word.to_string(),
+            weight,
+        })
+        .collect();
+    keywords.sort_by(|a, b| {
+        b.weight
+            .partial_cmp(&a.weight)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.word.cmp(&b.word))
+    });
+    keywords.truncate(top_k);
+    keywords
+}
+
+pub fn tfidf(words: &[&str], top_k: usize, idf: impl Fn(&str) -> f64) -> Vec<Keyword> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for word in words {
+        *counts.entry(word).or_default() += 1;
+    }
+    let total = words.len() as f64;
+    let weights = counts
+        .into_iter()
+        .map(|(word, count)| (word, count as f64 / total * idf(word)))
+        .collect();
+    top(weights, top_k)
+}
+
+// 权重归一化到 (0, 1]
+pub fn textrank(words: &[&str], top_k: usize) -> Vec<Keyword> {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut nodes: Vec<&str> = Vec::new();
+    let sequence: Vec<usize> = words
+        .iter()
+        .map(|&word| {
+            *ids.entry(word).or_insert_with(|| {
+                nodes.push(word);
+                nodes.len() - 1
+            })
+        })
+        .collect();
+
+    let mut edges: Vec<HashMap<usize, f64>> = vec![HashMap::new(); nodes.len()];
+    for (i, &from) in sequence.iter().enumerate() {
+        for &to in sequence.iter().skip(i + 1).take(WINDOW - 1) {
+            if from != to {
+                *edges[from].entry(to).or_default() += 1.0;
+                *edges[to].entry(from).or_default() += 1.0;
+            }
+        }
+    }
+    let out: Vec<f64> = edges.iter().map(|edges| edges.values().sum()).collect();
+
+    let mut scores = vec![1.0; nodes.len()];
+    for _ in 0..ITERATIONS {
+        scores = edges
+            .iter()
+            .map(|neighbors| {
+                let rank: f64 = neighbors
+                    .iter()
+                    .map(|(&j, weight)| weight / out[j] * scores[j])
+                    .sum();
+                (1.0 - DAMPING) + DAMPING * rank
+            })
+            .collect();
+    }
+    let max = scores.iter().cloned().fold(f64::MIN_POSITIVE, f64::max);
+    let weights = nodes
+        .into_iter()
+        .zip(scores)
+        .map(|(word, score)| (word, score / max))
+        .collect();
+    top(weights, top_k)
+}
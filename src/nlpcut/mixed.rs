@@ -0,0 +1,137 @@
+// 中英文混排切词：连续的中日韩文字交给 jieba，其余部分按字母数字切词、转小写并提取英文词干，
+// 这样 "Searching" 和 "search" 得到相同的词项。
+// 位置按 jieba 的字符位置和英文的词序依次累加，索引和查询时的短语位置一致
+use super::tokenizer::{JiebaTokenStream, JiebaTokenizer};
+use tantivy::tokenizer::{
+    Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer, Token,
+    TokenStream, Tokenizer,
+};
+
+// 超过这个字节数的英文词不索引，和 tantivy 默认分词器一致
+const MAX_WORD_LEN: usize = 40;
+
+pub fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        // 平假名、片假名
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        // 谚文
+        | '\u{ac00}'..='\u{d7af}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2ffff}')
+}
+
+// 词干提取要求小写输入，所以和转小写一起做；不提取词干时大小写由字段的归一化选项决定
+fn latin_analyzer(stem: bool) -> TextAnalyzer {
+    let builder = TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(RemoveLongFilter::limit(MAX_WORD_LEN));
+    if stem {
+        builder
+            .filter(LowerCaser)
+            .filter(Stemmer::new(Language::English))
+            .build()
+    } else {
+        builder.build()
+    }
+}
+
+// 英文词转小写后的词干，和索引中的英文词项一致
+pub fn stem(word: &str) -> String {
+    let mut analyzer = latin_analyzer(true);
+    let mut stream = analyzer.token_stream(word);
+    let mut stems = Vec::new();
+    while let Some(token) = stream.next() {
+        stems.push(token.text.clone());
+    }
+    stems.join(" ")
+}
+
+#[derive(Clone)]
+pub struct MixedTokenizer {
+    jieba: JiebaTokenizer,
+    latin: TextAnalyzer,
+}
+
+impl MixedTokenizer {
+    pub fn new(jieba: JiebaTokenizer) -> Self {
+        Self {
+            jieba,
+            latin: latin_analyzer(true),
+        }
+    }
+
+    // 英文只切词，不转小写也不提取词干
+    pub fn without_stemming(jieba: JiebaTokenizer) -> Self {
+        Self {
+            jieba,
+            latin: latin_analyzer(false),
+        }
+    }
+}
+
+// 把文本切成连续的中日韩文字段和其他段：(字节位置, 是否中日韩文字, 文本)
+fn runs(text: &str) -> Vec<(usize, bool, &str)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut current = None;
+    for (offset, ch) in text.char_indices() {
+        let cjk = is_cjk(ch);
+        if let Some(previous) = current.filter(|&previous| previous != cjk) {
+            runs.push((start, previous, &text[start..offset]));
+            start = offset;
+        }
+        current = Some(cjk);
+    }
+    if let Some(cjk) = current {
+        runs.push((start, cjk, &text[start..]));
+    }
+    runs
+}
+
+fn push_tokens(
+    stream: &mut impl TokenStream,
+    start: usize,
+    position: usize,
+    tokens: &mut Vec<Token>,
+) -> usize {
+    let mut next = position;
+    while let Some(token) = stream.next() {
+        next = next.max(position + token.position + token.position_length);
+        tokens.push(Token {
+            offset_from: start + token.offset_from,
+            offset_to: start + token.offset_to,
+            position: position + token.position,
+            text: token.text.clone(),
+            position_length: token.position_length,
+        });
+    }
+    next
+}
+
+impl Tokenizer for MixedTokenizer {
+    type TokenStream<'a> = JiebaTokenStream;
+
+    fn token_stream(&mut self, text: &str) -> JiebaTokenStream {
+        let mut tokens = Vec::new();
+        let mut position = 0;
+        for (start, cjk, run) in runs(text) {
+            position = if cjk {
+                push_tokens(
+                    &mut self.jieba.token_stream(run),
+                    start,
+                    position,
+                    &mut tokens,
+                )
+            } else {
+                push_tokens(
+                    &mut self.latin.token_stream(run),
+                    start,
+                    position,
+                    &mut tokens,
+                )
+            };
+        }
+        JiebaTokenStream::new(tokens)
+    }
+}
@@ -1 +1,20 @@
+pub mod classify;
+pub mod cut;
+pub mod keywords;
+pub mod mixed;
+pub mod normalize;
+pub mod pinyin;
+pub mod simhash;
 pub mod stopwords;
+pub mod t2s;
+pub mod tokenizer;
+pub mod url;
+
+pub use self::pinyin::{to_pinyin, PinyinTokenizer};
+pub use classify::{Classifier, Prediction};
+pub use cut::{CutMode, CutOptions};
+pub use keywords::{Keyword, KeywordMethod};
+pub use mixed::MixedTokenizer;
+pub use normalize::Normalizer;
+pub use tokenizer::{load_jieba, JiebaTokenizer};
+pub use url::UrlTokenizer;
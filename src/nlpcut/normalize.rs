@@ -0,0 +1,181 @@
+// 文本归一化：全角转半角、NFKC、繁体转简体、拉丁字母转小写，
+// 让 "臺灣" 和 "台湾"、"ＡＢＣ" 和 "abc" 得到相同的词项。
+// 作为 tantivy 的 TokenFilter 使用时先归一化整段文本再交给 jieba 切词，
+// 这样繁体和简体的切分结果一致；词的 offset 映射回原文
+use super::t2s::T2S;
+use serde_derive::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Normalizer {
+    pub traditional: bool,
+    pub full_width: bool,
+    pub nfkc: bool,
+    pub lowercase: bool,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self {
+            traditional: true,
+            full_width: true,
+            nfkc: true,
+            lowercase: true,
+        }
+    }
+}
+
+pub fn to_simplified(ch: char) -> char {
+    match T2S.binary_search_by_key(&ch, |&(traditional, _)| traditional) {
+        Ok(i) => T2S[i].1,
+        Err(_) => ch,
+    }
+}
+
+pub fn to_half_width(ch: char) -> char {
+    match ch {
+        '\u{3000}' => ' ',
+        '\u{ff01}'..='\u{ff5e}' => char::from_u32(ch as u32 - 0xfee0).unwrap_or(ch),
+        _ => ch,
+    }
+}
+
+impl Normalizer {
+    pub fn disabled() -> Self {
+        Self {
+            traditional: false,
+            full_width: false,
+            nfkc: false,
+            lowercase: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.traditional || self.full_width || self.nfkc || self.lowercase
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        self.normalize_with_offsets(text).0
+    }
+
+    // 按字符（连同后面的组合字符）逐段归一化，返回归一化后的文本，
+    // 以及每段在归一化文本和原文中的字节位置，最后一项是两者的结尾
+    fn normalize_with_offsets(&self, text: &str) -> (String, Vec<(usize, usize)>) {
+        let mut normalized = String::with_capacity(text.len());
+        let mut offsets = Vec::new();
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, ch)) = chars.next() {
+            let mut end = start + ch.len_utf8();
+            while let Some(&(offset, next)) = chars.peek() {
+                if canonical_combining_class(next) == 0 {
+                    break;
+                }
+                end = offset + next.len_utf8();
+                chars.next();
+            }
+            offsets.push((normalized.len(), start));
+            self.normalize_cluster(&text[start..end], &mut normalized);
+        }
+        offsets.push((normalized.len(), text.len()));
+        (normalized, offsets)
+    }
+
+    fn normalize_cluster(&self, cluster: &str, output: &mut String) {
+        let mut text: String = if self.full_width {
+            cluster.chars().map(to_half_width).collect()
+        } else {
+            cluster.to_string()
+        };
+        if self.nfkc {
+            text = text.nfkc().collect();
+        }
+        if self.traditional {
+            text = text.chars().map(to_simplified).collect();
+        }
+        if self.lowercase {
+            text = text.to_lowercase();
+        }
+        output.push_str(&text);
+    }
+}
+
+impl TokenFilter for Normalizer {
+    type Tokenizer<T: Tokenizer> = NormalizerFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> NormalizerFilter<T> {
+        NormalizerFilter {
+            normalizer: self,
+            tokenizer,
+            text: String::new(),
+            offsets: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NormalizerFilter<T> {
+    normalizer: Normalizer,
+    tokenizer: T,
+    text: String,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl<T: Tokenizer> Tokenizer for NormalizerFilter<T> {
+    type TokenStream<'a> = NormalizerTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        (self.text, self.offsets) = self.normalizer.normalize_with_offsets(text);
+        NormalizerTokenStream {
+            tail: self.tokenizer.token_stream(&self.text),
+            offsets: &self.offsets,
+        }
+    }
+}
+
+pub struct NormalizerTokenStream<'a, T> {
+    tail: T,
+    offsets: &'a [(usize, usize)],
+}
+
+impl<T> NormalizerTokenStream<'_, T> {
+    // 归一化文本中的位置对应的原文位置：起点取所在段的开头，终点取所在段的结尾
+    fn original(&self, offset: usize, end: bool) -> usize {
+        match self
+            .offsets
+            .binary_search_by_key(&offset, |&(normalized, _)| normalized)
+        {
+            Ok(i) => self.offsets[i].1,
+            Err(i) if end => self.offsets[i.min(self.offsets.len() - 1)].1,
+            Err(i) => self.offsets[i.saturating_sub(1)].1,
+        }
+    }
+}
+
+impl<T: TokenStream> TokenStream for NormalizerTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let (from, to) = {
+            let token = self.tail.token();
+            (token.offset_from, token.offset_to)
+        };
+        let offset_from = self.original(from, false);
+        let offset_to = self.original(to, true).max(offset_from);
+        let token = self.tail.token_mut();
+        token.offset_from = offset_from;
+        token.offset_to = offset_to;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
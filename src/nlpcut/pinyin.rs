@@ -0,0 +1,127 @@
+// 拼音分词：每个汉字转成一个不带声调的拼音音节，位置为字符下标，"谢娜" -> "xie" "na"；
+// 拉丁字母和数字转成小写原样保留。拼音输入 "xiena" 切成音节后按短语匹配，
+// 这样不依赖 jieba 对人名等未登录词的切分
+use super::tokenizer::JiebaTokenStream;
+use pinyin::ToPinyin;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+// 最长的音节是 zhuang/chuang/shuang
+const MAX_SYLLABLE_LEN: usize = 6;
+
+// 所有合法的拼音音节，从 CJK 统一汉字区间收集
+static SYLLABLES: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    ('\u{4e00}'..='\u{9fff}')
+        .filter_map(|ch| ch.to_pinyin())
+        .map(|pinyin| pinyin.plain())
+        .collect()
+});
+
+// 单个汉字的拼音，非汉字返回 None
+pub fn char_syllable(ch: char) -> Option<&'static str> {
+    ch.to_pinyin().map(|pinyin| pinyin.plain())
+}
+
+// 没有拼音的字符（标点、空白）被丢弃
+pub fn to_pinyin(text: &str) -> String {
+    let mut res = String::with_capacity(text.len() * 2);
+    for ch in text.chars() {
+        match char_syllable(ch) {
+            Some(syllable) => res.push_str(syllable),
+            None if ch.is_alphanumeric() => res.extend(ch.to_lowercase()),
+            None => {}
+        }
+    }
+    res
+}
+
+pub fn is_syllable(text: &str) -> bool {
+    SYLLABLES.contains(text)
+}
+
+// 把连写的拼音按最长匹配切成音节，"xiena" -> ["xie", "na"]；
+// 结尾不完整的音节（例如正在输入的 "beijingd" 中的 "d"）作为第二个返回值，
+// 无法切分时返回 None
+pub fn split_syllables(text: &str) -> Option<(Vec<String>, Option<String>)> {
+    let text = text.to_lowercase();
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let mut syllables = Vec::new();
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let longest = (1..=rest.len().min(MAX_SYLLABLE_LEN))
+            .rev()
+            .find(|&len| is_syllable(&rest[..len]));
+        match longest {
+            Some(len) => {
+                syllables.push(rest[..len].to_string());
+                rest = &rest[len..];
+            }
+            None if SYLLABLES.iter().any(|s| s.starts_with(rest)) => {
+                return Some((syllables, Some(rest.to_string())));
+            }
+            None => return None,
+        }
+    }
+    Some((syllables, None))
+}
+
+// 一个查询词的拼音音节：汉字逐字转换，连写的拼音切分成音节，无法切分的拉丁字母词被忽略；
+// 第二个返回值是结尾不完整的音节
+pub fn pinyin_syllables(word: &str) -> (Vec<String>, Option<String>) {
+    let mut syllables = Vec::new();
+    let mut partial = None;
+    let mut stream = PinyinTokenizer.token_stream(word);
+    while let Some(token) = stream.next() {
+        partial = None;
+        if is_syllable(&token.text) || !token.text.chars().all(|c| c.is_ascii_lowercase()) {
+            syllables.push(token.text.clone());
+            continue;
+        }
+        if let Some((split, rest)) = split_syllables(&token.text) {
+            syllables.extend(split);
+            partial = rest;
+        }
+    }
+    (syllables, partial)
+}
+
+#[derive(Clone, Default)]
+pub struct PinyinTokenizer;
+
+impl Tokenizer for PinyinTokenizer {
+    type TokenStream<'a> = JiebaTokenStream;
+
+    fn token_stream(&mut self, text: &str) -> JiebaTokenStream {
+        let mut tokens: Vec<Token> = Vec::new();
+        // 连续的字母数字作为一个词
+        let mut word: Option<Token> = None;
+        for (position, (offset, ch)) in text.char_indices().enumerate() {
+            let offset_to = offset + ch.len_utf8();
+            if ch.is_ascii_alphanumeric() {
+                let token = word.get_or_insert_with(|| Token {
+                    offset_from: offset,
+                    position,
+                    ..Default::default()
+                });
+                token.text.push(ch.to_ascii_lowercase());
+                token.offset_to = offset_to;
+                continue;
+            }
+            tokens.extend(word.take());
+            if let Some(syllable) = char_syllable(ch) {
+                tokens.push(Token {
+                    offset_from: offset,
+                    offset_to,
+                    position,
+                    text: syllable.to_string(),
+                    position_length: 1,
+                });
+            }
+        }
+        tokens.extend(word);
+        JiebaTokenStream::new(tokens)
+    }
+}
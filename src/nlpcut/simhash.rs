@@ -0,0 +1,53 @@
+// SimHash 文本指纹：jieba 切词去掉停用词后，每个词的 64 位哈希按词频对每一位投票，
+// 内容几乎相同的文本（转载时改了几个字）指纹只差几位，用汉明距离判断是否重复
+use super::classify::features;
+use jieba_rs::Jieba;
+use std::collections::HashMap;
+
+// FNV-1a，指纹会持久化到数据库和索引，不能用每次运行可能不同的 std 哈希
+fn fnv1a(word: &str) -> u64 {
+    word.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// 没有任何有效词时返回 0
+pub fn simhash(jieba: &Jieba, text: &str) -> u64 {
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for word in features(jieba, text) {
+        *counts.entry(word).or_default() += 1;
+    }
+    let mut votes = [0i64; 64];
+    for (word, count) in &counts {
+        let hash = fnv1a(word);
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *vote += count;
+            } else {
+                *vote -= count;
+            }
+        }
+    }
+    votes
+        .iter()
+        .enumerate()
+        .filter(|(_, vote)| **vote > 0)
+        .fold(0, |hash, (bit, _)| hash | 1 << bit)
+}
+
+// 指纹切成 4 段，每段 16 位；汉明距离不超过 3 的两个指纹至少有一段完全相同，
+// 按段精确查找就能找到所有候选，不用和每篇文档比较
+pub const BANDS: u32 = 4;
+const BAND_BITS: u32 = 64 / BANDS;
+
+// 每段的值，高位是段号，不同段的值不会相同
+pub fn bands(simhash: u64) -> impl Iterator<Item = u64> {
+    (0..BANDS).map(move |band| {
+        let value = simhash >> (band * BAND_BITS) & ((1 << BAND_BITS) - 1);
+        u64::from(band) << BAND_BITS | value
+    })
+}
+
+pub fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
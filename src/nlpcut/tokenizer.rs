@@ -0,0 +1,85 @@
+use jieba_rs::{Jieba, TokenizeMode};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+// 加载 jieba 默认词典以及用户词典
+pub fn load_jieba(dict_paths: &[PathBuf]) -> io::Result<Jieba> {
+    let mut jieba = Jieba::new();
+    for path in dict_paths {
+        let mut reader = BufReader::new(File::open(path)?);
+        jieba.load_dict(&mut reader).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })?;
+    }
+    Ok(jieba)
+}
+
+// 与 tantivy_jieba::JiebaTokenizer 相同的切词方式，但共享同一个配置好的 Jieba 实例
+#[derive(Clone)]
+pub struct JiebaTokenizer {
+    jieba: Arc<Jieba>,
+}
+
+impl JiebaTokenizer {
+    pub fn new(jieba: Arc<Jieba>) -> Self {
+        Self { jieba }
+    }
+}
+
+pub struct JiebaTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl JiebaTokenStream {
+    pub(crate) fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, index: 0 }
+    }
+}
+
+impl TokenStream for JiebaTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+impl Tokenizer for JiebaTokenizer {
+    type TokenStream<'a> = JiebaTokenStream;
+
+    fn token_stream(&mut self, text: &str) -> JiebaTokenStream {
+        let mut indices = text.char_indices().collect::<Vec<_>>();
+        indices.push((text.len(), '\0'));
+        let tokens = self
+            .jieba
+            .tokenize(text, TokenizeMode::Search, true)
+            .into_iter()
+            .map(|token| Token {
+                offset_from: indices[token.start].0,
+                offset_to: indices[token.end].0,
+                position: token.start,
+                text: text[indices[token.start].0..indices[token.end].0].to_string(),
+                position_length: token.end - token.start,
+            })
+            .collect();
+        JiebaTokenStream::new(tokens)
+    }
+}
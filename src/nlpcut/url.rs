@@ -0,0 +1,132 @@
+// 网址切词：去掉协议后把主机名、路径的每一段和查询参数的键、值分别交给 parts 切词，
+// 路径和参数先做百分号解码，这样搜索 "github" 能匹配 https://github.com/...
+use super::tokenizer::JiebaTokenStream;
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::ops::Range;
+use tantivy::tokenizer::{TextAnalyzer, Token, TokenStream, Tokenizer};
+
+#[derive(Clone)]
+pub struct UrlTokenizer {
+    parts: TextAnalyzer,
+}
+
+impl UrlTokenizer {
+    pub fn new(parts: TextAnalyzer) -> Self {
+        Self { parts }
+    }
+}
+
+fn decode(text: &str, query: bool) -> Cow<'_, str> {
+    let text = if query && text.contains('+') {
+        Cow::Owned(text.replace('+', " "))
+    } else {
+        Cow::Borrowed(text)
+    };
+    match percent_decode_str(&text).decode_utf8_lossy() {
+        Cow::Borrowed(_) => text,
+        Cow::Owned(decoded) => Cow::Owned(decoded),
+    }
+}
+
+// 主机名在原文中的位置（去掉协议、用户信息和端口）以及主机部分的结束位置
+fn host_range(url: &str) -> (Range<usize>, usize) {
+    let start = match url.find("://") {
+        Some(i)
+            if url[..i]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)) =>
+        {
+            i + 3
+        }
+        _ => 0,
+    };
+    let host_end = url[start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| start + i);
+    // 去掉用户信息和端口
+    let host_start = url[start..host_end]
+        .rfind('@')
+        .map_or(start, |i| start + i + 1);
+    let port = url[host_start..host_end]
+        .find(':')
+        .map_or(host_end, |i| host_start + i);
+    (host_start..port, host_end)
+}
+
+// 小写的主机名，用于按网站分组
+pub fn host(url: &str) -> String {
+    let (range, _) = host_range(url);
+    url[range].to_lowercase()
+}
+
+// 网址各部分在原文中的位置和解码后的文本
+fn url_parts(url: &str) -> Vec<(Range<usize>, Cow<'_, str>)> {
+    let mut parts = Vec::new();
+    let mut push = |range: Range<usize>, query: bool| {
+        if !range.is_empty() {
+            parts.push((range.clone(), decode(&url[range], query)));
+        }
+    };
+
+    let (host, host_end) = host_range(url);
+    push(host, false);
+
+    let fragment = url[host_end..]
+        .find('#')
+        .map_or(url.len(), |i| host_end + i);
+    let query = url[host_end..fragment]
+        .find('?')
+        .map_or(fragment, |i| host_end + i);
+    let mut offset = host_end;
+    for segment in url[host_end..query].split('/') {
+        push(offset..offset + segment.len(), false);
+        offset += segment.len() + 1;
+    }
+    if query < fragment {
+        offset = query + 1;
+        for param in url[query + 1..fragment].split(['&', ';']) {
+            let key = param.find('=').unwrap_or(param.len());
+            push(offset..offset + key, true);
+            if key < param.len() {
+                push(offset + key + 1..offset + param.len(), true);
+            }
+            offset += param.len() + 1;
+        }
+    }
+    if fragment < url.len() {
+        push(fragment + 1..url.len(), false);
+    }
+    parts
+}
+
+impl Tokenizer for UrlTokenizer {
+    type TokenStream<'a> = JiebaTokenStream;
+
+    fn token_stream(&mut self, url: &str) -> JiebaTokenStream {
+        let mut tokens = Vec::new();
+        for (range, text) in url_parts(url) {
+            // 解码后长度不变时词的位置可以直接换算，否则取整个部分的位置
+            let same = text.len() == range.len();
+            let mut stream = self.parts.token_stream(&text);
+            while let Some(token) = stream.next() {
+                let (offset_from, offset_to) = if same {
+                    (
+                        range.start + token.offset_from,
+                        range.start + token.offset_to,
+                    )
+                } else {
+                    (range.start, range.end)
+                };
+                tokens.push(Token {
+                    offset_from,
+                    offset_to,
+                    position: tokens.len(),
+                    text: token.text.clone(),
+                    position_length: 1,
+                });
+            }
+        }
+        JiebaTokenStream::new(tokens)
+    }
+}
@@ -0,0 +1,165 @@
+// 令牌桶限流：带凭证的请求按调用方计数，其余按客户端 IP 计数
+use crate::config::{LimitsConfig, Quota};
+use crate::error::AppError;
+use crate::server::AppState;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{header, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+// 最多跟踪的桶数，超过时淘汰最久没有使用的桶，避免大量 IP 占满内存
+pub const MAX_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    // 在 recent 中的位置
+    seq: u64,
+}
+
+// 按最近使用的顺序排列的桶：闲置到已经回满的桶和新桶没有区别，直接淘汰；
+// 数量达到上限时再淘汰最久没有使用的桶。每次请求只做 O(log n) 的操作
+#[derive(Default)]
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    recent: BTreeMap<u64, String>,
+    next_seq: u64,
+}
+
+impl Buckets {
+    fn evict(&mut self, key: &str, now: Instant, full_after: Duration) {
+        while let Some((_, oldest)) = self.recent.first_key_value() {
+            let idle = now.saturating_duration_since(self.map[oldest].updated);
+            let over = self.map.len() >= MAX_BUCKETS && !self.map.contains_key(key);
+            if idle < full_after && !over {
+                break;
+            }
+            if let Some((_, oldest)) = self.recent.pop_first() {
+                self.map.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &str, full: f64, now: Instant) -> &mut Bucket {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.recent.insert(seq, key.to_string());
+        let bucket = self.map.entry(key.to_string()).or_insert(Bucket {
+            tokens: full,
+            updated: now,
+            seq,
+        });
+        if bucket.seq != seq {
+            self.recent.remove(&bucket.seq);
+            bucket.seq = seq;
+        }
+        bucket
+    }
+}
+
+pub struct RateLimiter {
+    quota: Quota,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    // 当前跟踪的桶数
+    pub fn tracked(&self) -> usize {
+        let buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        buckets.map.len()
+    }
+
+    // 取一个令牌；桶空时返回需要等待的时间
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        if !self.quota.enabled() {
+            return Ok(());
+        }
+        let Quota { rate, burst } = self.quota;
+        let burst = f64::from(burst);
+        // 空桶闲置这么久就回满了
+        let full_after = Duration::try_from_secs_f64(burst / rate).unwrap_or(Duration::MAX);
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        buckets.evict(key, now, full_after);
+        let bucket = buckets.touch(key, burst, now);
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+pub struct Limiter {
+    per_ip: RateLimiter,
+    per_key: RateLimiter,
+}
+
+impl Limiter {
+    pub fn from_config(config: &LimitsConfig) -> Self {
+        Self {
+            per_ip: RateLimiter::new(config.per_ip),
+            per_key: RateLimiter::new(config.per_key),
+        }
+    }
+}
+
+pub async fn rate_limit<B>(
+    State(state): State<AppState>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    // 凭证无效时按 IP 限流，由后面的认证中间件拒绝
+    let principal = state.auth.authenticate(req.headers()).ok().flatten();
+    let res = match &principal {
+        Some(principal) => state.limiter.per_key.check(&principal.id),
+        None => {
+            // 通过 into_make_service_with_connect_info 启动时才有客户端地址
+            let ip = req
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+                .unwrap_or_default();
+            state.limiter.per_ip.check(&ip)
+        }
+    };
+    match res {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            tracing::debug!(
+                principal = principal.as_ref().map(|p| p.id.as_str()),
+                retry_after,
+                "rate limited"
+            );
+            let mut response = AppError::too_many_requests_msg(&format!(
+                "rate limit exceeded, retry after {} seconds",
+                retry_after
+            ))
+            .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
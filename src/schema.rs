@@ -8,5 +8,7 @@ diesel::table! {
         content -> Text,
         doc_type -> Varchar,
         published -> Bool,
+        simhash -> Nullable<Int8>,
+        duplicate_of -> Nullable<Int4>,
     }
 }
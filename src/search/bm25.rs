@@ -0,0 +1,234 @@
+// tantivy 的 BM25 把 k1/b 写死为 1.2/0.75，这里实现一个参数可配置的词项查询
+use serde_derive::{Deserialize, Serialize};
+use tantivy::fieldnorm::FieldNormReader;
+use tantivy::postings::SegmentPostings;
+use tantivy::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, TermQuery, Weight};
+use tantivy::schema::IndexRecordOption;
+use tantivy::{DocId, DocSet, Postings, Score, SegmentReader, TantivyError, Term};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bm25Params {
+    // 词频饱和度，越大词频影响越大
+    pub k1: Score,
+    // 文档长度归一化程度，0 表示不考虑长度
+    pub b: Score,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bm25TermQuery {
+    term: Term,
+    params: Bm25Params,
+    boost: Score,
+}
+
+impl Bm25TermQuery {
+    pub fn new(term: Term, params: Bm25Params, boost: Score) -> Self {
+        Self {
+            term,
+            params,
+            boost,
+        }
+    }
+
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
+    pub fn boost(&self) -> Score {
+        self.boost
+    }
+}
+
+impl Query for Bm25TermQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let EnableScoring::Enabled {
+            statistics_provider: stats,
+            ..
+        } = enable_scoring
+        else {
+            // 不需要打分时（例如只计数）和普通词项查询一样
+            return TermQuery::new(self.term.clone(), IndexRecordOption::Basic)
+                .weight(enable_scoring);
+        };
+        let total_num_docs = stats.total_num_docs()?;
+        let total_num_tokens = stats.total_num_tokens(self.term.field())?;
+        let doc_freq = stats.doc_freq(&self.term)?;
+        let avg_fieldnorm = if total_num_docs == 0 {
+            0.0
+        } else {
+            total_num_tokens as Score / total_num_docs as Score
+        };
+        Ok(Box::new(Bm25TermWeight {
+            term: self.term.clone(),
+            params: self.params,
+            boost: self.boost,
+            doc_freq,
+            total_num_docs,
+            idf: idf(doc_freq, total_num_docs),
+            avg_fieldnorm,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        visitor(&self.term, false);
+    }
+}
+
+fn idf(doc_freq: u64, doc_count: u64) -> Score {
+    let doc_freq = doc_freq.min(doc_count);
+    let x = ((doc_count - doc_freq) as Score + 0.5) / (doc_freq as Score + 0.5);
+    (1.0 + x).ln()
+}
+
+struct Bm25TermWeight {
+    term: Term,
+    params: Bm25Params,
+    boost: Score,
+    doc_freq: u64,
+    total_num_docs: u64,
+    idf: Score,
+    avg_fieldnorm: Score,
+}
+
+impl Bm25TermWeight {
+    fn term_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> tantivy::Result<Option<Bm25TermScorer>> {
+        let field = self.term.field();
+        let fieldnorms = reader.get_fieldnorms_reader(field)?;
+        let postings = reader
+            .inverted_index(field)?
+            .read_postings(&self.term, IndexRecordOption::WithFreqs)?;
+        Ok(postings.map(|postings| Bm25TermScorer {
+            postings,
+            fieldnorms,
+            weight: Bm25 {
+                params: self.params,
+                idf: self.idf,
+                avg_fieldnorm: self.avg_fieldnorm,
+                boost: self.boost * boost,
+            },
+        }))
+    }
+}
+
+impl Weight for Bm25TermWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        match self.term_scorer(reader, boost)? {
+            Some(scorer) => Ok(Box::new(scorer)),
+            None => Ok(Box::new(EmptyScorer)),
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let not_matched =
+            || TantivyError::InvalidArgument(format!("Document #({}) does not match", doc));
+        let mut scorer = self.term_scorer(reader, 1.0)?.ok_or_else(not_matched)?;
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(not_matched());
+        }
+        let term_freq = scorer.postings.term_freq();
+        let fieldnorm = scorer.fieldnorms.fieldnorm(doc);
+        let weight = &scorer.weight;
+        let field_name = reader.schema().get_field_name(self.term.field());
+        let value = self.term.value();
+        let text = value.as_str().unwrap_or_default();
+
+        let mut explanation = Explanation::new(
+            format!("bm25 {}:{}, product of:", field_name, text),
+            weight.score(fieldnorm, term_freq),
+        );
+        explanation.add_const("boost", weight.boost);
+
+        let mut idf = Explanation::new(
+            "idf, computed as ln(1 + (N - n + 0.5) / (n + 0.5)) from:",
+            weight.idf,
+        );
+        idf.add_const(
+            "n, number of docs containing this term",
+            self.doc_freq as Score,
+        );
+        idf.add_const("N, total number of docs", self.total_num_docs as Score);
+        explanation.add_detail(idf);
+
+        let mut tf = Explanation::new(
+            "tf, computed as freq * (k1 + 1) / (freq + k1 * (1 - b + b * dl / avgdl)) from:",
+            weight.tf(fieldnorm, term_freq),
+        );
+        tf.add_const(
+            "freq, occurrences of term within document",
+            term_freq as Score,
+        );
+        tf.add_const("k1, term saturation parameter", weight.params.k1);
+        tf.add_const("b, length normalization parameter", weight.params.b);
+        tf.add_const("dl, length of field", fieldnorm as Score);
+        tf.add_const("avgdl, average length of field", weight.avg_fieldnorm);
+        explanation.add_detail(tf);
+        Ok(explanation)
+    }
+}
+
+struct Bm25 {
+    params: Bm25Params,
+    idf: Score,
+    avg_fieldnorm: Score,
+    boost: Score,
+}
+
+impl Bm25 {
+    fn tf(&self, fieldnorm: u32, term_freq: u32) -> Score {
+        let Bm25Params { k1, b } = self.params;
+        let term_freq = term_freq as Score;
+        let length = if self.avg_fieldnorm > 0.0 {
+            fieldnorm as Score / self.avg_fieldnorm
+        } else {
+            1.0
+        };
+        term_freq * (k1 + 1.0) / (term_freq + k1 * (1.0 - b + b * length))
+    }
+
+    fn score(&self, fieldnorm: u32, term_freq: u32) -> Score {
+        self.boost * self.idf * self.tf(fieldnorm, term_freq)
+    }
+}
+
+struct Bm25TermScorer {
+    postings: SegmentPostings,
+    fieldnorms: FieldNormReader,
+    weight: Bm25,
+}
+
+impl DocSet for Bm25TermScorer {
+    fn advance(&mut self) -> DocId {
+        self.postings.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.postings.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.postings.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.postings.size_hint()
+    }
+}
+
+impl Scorer for Bm25TermScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.doc();
+        let fieldnorm = self.fieldnorms.fieldnorm(doc);
+        self.weight.score(fieldnorm, self.postings.term_freq())
+    }
+}
@@ -0,0 +1,98 @@
+// 按 fast field 折叠搜索结果：每组只保留得分最高的文档，并记录同组还有多少篇匹配
+use std::collections::HashMap;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::Column;
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+pub struct CollapseCollector {
+    field: String,
+    limit: usize,
+    offset: usize,
+}
+
+impl CollapseCollector {
+    pub fn new(field: &str, limit: usize, offset: usize) -> Self {
+        Self {
+            field: field.to_string(),
+            limit,
+            offset,
+        }
+    }
+}
+
+// 每组得分最高的文档和组内匹配的文档数
+type Groups = HashMap<u64, (Score, DocAddress, usize)>;
+
+pub struct CollapseSegmentCollector {
+    column: Column<u64>,
+    segment: SegmentOrdinal,
+    groups: Groups,
+}
+
+impl SegmentCollector for CollapseSegmentCollector {
+    type Fruit = Groups;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let Some(group) = self.column.first(doc) else {
+            return;
+        };
+        let address = DocAddress::new(self.segment, doc);
+        let best = self.groups.entry(group).or_insert((score, address, 0));
+        best.2 += 1;
+        if score > best.0 {
+            (best.0, best.1) = (score, address);
+        }
+    }
+
+    fn harvest(self) -> Groups {
+        self.groups
+    }
+}
+
+impl Collector for CollapseCollector {
+    // 按得分排序的 (得分, 文档, 组内其他匹配文档数)，以及组数
+    type Fruit = (Vec<(Score, DocAddress, usize)>, usize);
+    type Child = CollapseSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> tantivy::Result<CollapseSegmentCollector> {
+        Ok(CollapseSegmentCollector {
+            column: reader.fast_fields().u64(&self.field)?,
+            segment,
+            groups: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, fruits: Vec<Groups>) -> tantivy::Result<Self::Fruit> {
+        let mut merged = Groups::new();
+        for groups in fruits {
+            for (group, (score, address, count)) in groups {
+                let best = merged.entry(group).or_insert((score, address, 0));
+                best.2 += count;
+                if score > best.0 {
+                    (best.0, best.1) = (score, address);
+                }
+            }
+        }
+        let total = merged.len();
+        let mut hits: Vec<(Score, DocAddress, usize)> = merged
+            .into_values()
+            .map(|(score, address, count)| (score, address, count - 1))
+            .collect();
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        Ok((
+            hits.into_iter()
+                .skip(self.offset)
+                .take(self.limit)
+                .collect(),
+            total,
+        ))
+    }
+}
@@ -0,0 +1,185 @@
+// 查询纠错（"您是不是要找"）：英文词在索引词典里找编辑距离最近的词，
+// 中文词按拼音在 pinyin 字段里找同音的词
+use super::schema::SearchFields;
+use crate::nlpcut::mixed::{is_cjk, stem};
+use crate::nlpcut::pinyin::char_syllable;
+use crate::Result;
+use std::collections::HashMap;
+use tantivy::collector::TopDocs;
+use tantivy::query::{BooleanQuery, Occur, PhraseQuery, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Searcher, Term};
+
+// 每个首字母最多比较的词典项数
+const MAX_CANDIDATE_TERMS: usize = 10_000;
+// 找同音词时读取的文档数
+const HOMOPHONE_DOCS: usize = 20;
+// 找英文词干对应的原词时读取的文档数
+const SURFACE_DOCS: usize = 10;
+
+// 含有查询语法的词不做纠错
+pub fn is_plain_word(word: &str) -> bool {
+    !matches!(word, "AND" | "OR" | "NOT")
+        && !word.starts_with(['+', '-'])
+        && !word.contains([
+            ':', '"', '(', ')', '[', ']', '{', '}', '^', '~', '*', '?', '\\',
+        ])
+}
+
+pub fn term_exists(searcher: &Searcher, fields: &[Field], text: &str) -> Result<bool> {
+    for field in fields {
+        if searcher.doc_freq(&Term::from_field_text(*field, text))? > 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// 与 word 首字母相同、编辑距离不超过 max_distance 的词中距离最小的，距离相同取文档数多的
+pub fn nearest_term(
+    searcher: &Searcher,
+    fields: &[Field],
+    word: &str,
+    max_distance: usize,
+) -> Result<Option<String>> {
+    let Some(first) = word.chars().next() else {
+        return Ok(None);
+    };
+    let mut first_bytes = [0; 4];
+    let first = first.encode_utf8(&mut first_bytes).as_bytes();
+    let len = word.chars().count();
+    let mut doc_freqs: HashMap<String, u64> = HashMap::new();
+    for segment in searcher.segment_readers() {
+        for field in fields {
+            let inverted_index = segment.inverted_index(*field)?;
+            let mut stream = inverted_index.terms().range().ge(first).into_stream()?;
+            let mut scanned = 0;
+            while scanned < MAX_CANDIDATE_TERMS && stream.advance() {
+                if !stream.key().starts_with(first) {
+                    break;
+                }
+                scanned += 1;
+                let Ok(term) = std::str::from_utf8(stream.key()) else {
+                    continue;
+                };
+                if term.chars().count().abs_diff(len) <= max_distance {
+                    *doc_freqs.entry(term.to_string()).or_default() +=
+                        u64::from(stream.value().doc_freq);
+                }
+            }
+        }
+    }
+    Ok(doc_freqs
+        .into_iter()
+        .map(|(term, doc_freq)| (levenshtein(word, &term), doc_freq, term))
+        .filter(|(distance, _, _)| *distance > 0 && *distance <= max_distance)
+        .min_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)))
+        .map(|(_, _, term)| term))
+}
+
+// 在拼音字段里按短语查找和 word 读音相同的文档，从标题和正文中取出对应的原文，出现最多的作为结果；
+// 原文中出现过 word 本身时说明它不是错别字，返回 None
+pub fn homophone(
+    searcher: &Searcher,
+    fields: &SearchFields,
+    word: &str,
+    syllables: &[String],
+) -> Result<Option<String>> {
+    let mut terms: Vec<Term> = syllables
+        .iter()
+        .map(|syllable| Term::from_field_text(fields.pinyin, syllable))
+        .collect();
+    let query: Box<dyn Query> = match terms.len() {
+        0 => return Ok(None),
+        1 => Box::new(TermQuery::new(terms.remove(0), IndexRecordOption::Basic)),
+        _ => Box::new(PhraseQuery::new(terms)),
+    };
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, address) in searcher.search(&query, &TopDocs::with_limit(HOMOPHONE_DOCS))? {
+        let doc = searcher.doc(address)?;
+        for field in [fields.title, fields.body] {
+            for value in doc.get_all(field) {
+                for text in same_pinyin(value.as_text().unwrap_or_default(), syllables) {
+                    *counts.entry(text).or_default() += 1;
+                }
+            }
+        }
+    }
+    if counts.contains_key(word) {
+        return Ok(None);
+    }
+    Ok(counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(text, _)| text))
+}
+
+// 英文词项是小写词干（如 televis），从包含它的标题和正文中找出词干相同、出现最多的原词，
+// 找不到时返回词项本身；不含英文字母的词项原样返回
+pub fn surface_form(searcher: &Searcher, fields: &SearchFields, term: &str) -> Result<String> {
+    let Some((last, _)) = term.char_indices().last() else {
+        return Ok(String::new());
+    };
+    if !term.chars().any(|c| c.is_alphabetic() && !is_cjk(c)) {
+        return Ok(term.to_string());
+    }
+    let query = BooleanQuery::new(
+        [fields.title, fields.body]
+            .into_iter()
+            .map(|field| {
+                let query =
+                    TermQuery::new(Term::from_field_text(field, term), IndexRecordOption::Basic);
+                (Occur::Should, Box::new(query) as Box<dyn Query>)
+            })
+            .collect(),
+    );
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, address) in searcher.search(&query, &TopDocs::with_limit(SURFACE_DOCS))? {
+        let doc = searcher.doc(address)?;
+        for field in [fields.title, fields.body] {
+            for value in doc.get_all(field) {
+                let text = value.as_text().unwrap_or_default();
+                for word in text.split(|c: char| !c.is_alphanumeric() || is_cjk(c)) {
+                    let word = word.to_lowercase();
+                    // 词干最多改动原词的最后一个字母，先按前缀过滤
+                    if word.starts_with(&term[..last]) && stem(&word) == term {
+                        *counts.entry(word).or_default() += 1;
+                    }
+                }
+            }
+        }
+    }
+    Ok(counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map_or_else(|| term.to_string(), |(word, _)| word))
+}
+
+// text 中拼音依次为 syllables 的连续汉字
+fn same_pinyin(text: &str, syllables: &[String]) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .windows(syllables.len())
+        .filter(|window| {
+            window
+                .iter()
+                .zip(syllables)
+                .all(|(ch, syllable)| char_syllable(*ch) == Some(syllable.as_str()))
+        })
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
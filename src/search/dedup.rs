@@ -0,0 +1,71 @@
+// 近似重复检测：写入时计算标题和正文的 SimHash，存到索引的 fast field，
+// 和已索引文档的指纹汉明距离不超过 threshold 的算作重复
+use super::service::DocSource;
+use crate::error::AppError;
+use crate::Result;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    // 只计算指纹，不检测重复
+    #[default]
+    Off,
+    // 拒绝写入，返回 409
+    Reject,
+    // 照常写入，记录重复的是哪篇文档
+    Flag,
+    // 在 flag 的基础上和原文档归为一组，搜索时可以用 collapse 折叠
+    Group,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Dedup {
+    pub mode: DedupMode,
+    // 64 位指纹中最多允许不同的位数，不能超过 simhash::BANDS - 1（即 3）：
+    // 候选文档按 16 位一段的指纹精确查找，相差更多位时可能每段都不同
+    pub threshold: u32,
+}
+
+impl Default for Dedup {
+    fn default() -> Self {
+        Self {
+            mode: DedupMode::Off,
+            threshold: 3,
+        }
+    }
+}
+
+impl Dedup {
+    // reject 模式下有重复时返回 409，details 里给出重复的文档和距离
+    pub fn check(&self, duplicate: Option<&Duplicate>) -> Result<()> {
+        match duplicate {
+            Some(duplicate) if self.mode == DedupMode::Reject => Err(AppError::conflict_msg(
+                &format!("near-duplicate of document {}", duplicate.id),
+            )
+            .with_details(serde_json::json!({
+                "duplicate_of": duplicate.id,
+                "distance": duplicate.distance,
+            }))),
+            _ => Ok(()),
+        }
+    }
+}
+
+// 写入前算好的指纹和最相近的重复文档，写入时直接使用，不再重复计算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fingerprint {
+    pub simhash: u64,
+    pub duplicate: Option<Duplicate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Duplicate {
+    // 最相近的已索引文档
+    pub id: u64,
+    pub source: DocSource,
+    // 它所在的组，组号是组里第一篇文档的 id，数据库文档的组号最高位为 1
+    pub group: u64,
+    pub distance: u32,
+}
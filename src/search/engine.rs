@@ -2,8 +2,11 @@ use crate::crawler;
 use crate::nlpcut::stopwords::STOPWORDS_CMN;
 use jieba_rs::Jieba;
 use std::collections::HashMap;
-pub fn exc_search() {
-    let data = crawler::read_file_data();
+use std::path::PathBuf;
+
+// 读取配置的语料目录（[crawler] roots）
+pub fn exc_search(roots: &[PathBuf]) {
+    let data = crawler::read_file_data_from(roots);
 
     let mut store: HashMap<i32, String> = HashMap::new();
     let mut terms: HashMap<String, Vec<i32>> = HashMap::new();
@@ -14,15 +17,15 @@ pub fn exc_search() {
         store.insert(abs_index, ads.clone());
         let words = jieba.cut(ads.as_str(), false);
         for w in words.clone() {
-            let tf: f32 = words.iter().filter(|&word| word == &w).count() as f32
-                / words.iter().count() as f32;
+            let tf: f32 =
+                words.iter().filter(|&word| word == &w).count() as f32 / words.len() as f32;
 
-            println!("{} word tf {}", abs_index, tf);
+            tracing::trace!("{} word tf {}", abs_index, tf);
             if STOPWORDS_CMN.contains(&w) {
                 continue;
             }
             let v: Vec<i32> = Vec::new();
-            let mut abs_indexs = terms.get(w).unwrap_or_else(|| &v).clone();
+            let mut abs_indexs = terms.get(w).unwrap_or(&v).clone();
             if abs_indexs.contains(&abs_index) {
                 continue;
             }
@@ -40,25 +43,19 @@ pub fn exc_search() {
 
     for search_term in jieba.cut("谢娜", false) {
         let search_res = terms.get(search_term);
-        println!("search xiena: {:?}", search_res);
-        match search_res {
-            Some(searchs) => {
-                for indexs in searchs {
-                    if !res_indexs.contains(&indexs) {
-                        res_indexs.push(indexs);
-                        match store.get(indexs) {
-                            Some(v) => {
-                                if !res.contains(&v) {
-                                    res.push(v)
-                                }
-                            }
-                            None => {}
+        tracing::debug!("search xiena: {:?}", search_res);
+        if let Some(searchs) = search_res {
+            for indexs in searchs {
+                if !res_indexs.contains(&indexs) {
+                    res_indexs.push(indexs);
+                    if let Some(v) = store.get(indexs) {
+                        if !res.contains(&v) {
+                            res.push(v)
                         }
                     }
                 }
             }
-            None => {}
         }
     }
-    println!("search res: {:?}", res);
+    tracing::info!("search res: {:?}", res);
 }
@@ -0,0 +1,171 @@
+// 按字符串 fast field 分组搜索结果：每组保留得分最高的 size 篇文档，并记录组内匹配的文档数。
+// 各段里组用词项序号做键，合并前换成字符串，不同段的序号不能直接比较
+use super::schema::{DOC_TYPE_FIELD, HOST_FIELD};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::StrColumn;
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+type Hit = (Score, DocAddress);
+
+// 可以分组的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    // 网址的主机名
+    Host,
+    DocType,
+}
+
+impl GroupBy {
+    pub fn field(self) -> &'static str {
+        match self {
+            GroupBy::Host => HOST_FIELD,
+            GroupBy::DocType => DOC_TYPE_FIELD,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    // 没有值的文档归到空字符串组
+    pub key: String,
+    // 组内匹配的文档数
+    pub count: usize,
+    // 按得分从高到低
+    pub hits: Vec<Hit>,
+}
+
+impl Group {
+    fn best(&self) -> Score {
+        self.hits.first().map_or(Score::NEG_INFINITY, |hit| hit.0)
+    }
+}
+
+// 多样化：各组保留的文档合并后重新按得分排序
+pub fn flatten(groups: Vec<Group>) -> Vec<Hit> {
+    let mut hits: Vec<Hit> = groups.into_iter().flat_map(|group| group.hits).collect();
+    hits.sort_by(by_score);
+    hits
+}
+
+pub struct GroupCollector {
+    field: String,
+    size: usize,
+}
+
+impl GroupCollector {
+    pub fn new(field: &str, size: usize) -> Self {
+        Self {
+            field: field.to_string(),
+            size,
+        }
+    }
+}
+
+fn by_score(a: &Hit, b: &Hit) -> std::cmp::Ordering {
+    b.0.total_cmp(&a.0).then(a.1.cmp(&b.1))
+}
+
+// 每组只保留前 size 篇；攒到两倍时再排序截断，避免每次都排序
+fn push_hit(hits: &mut Vec<Hit>, hit: Hit, size: usize) {
+    hits.push(hit);
+    if hits.len() >= size.max(1).saturating_mul(2) {
+        truncate(hits, size);
+    }
+}
+
+fn truncate(hits: &mut Vec<Hit>, size: usize) {
+    hits.sort_by(by_score);
+    hits.truncate(size);
+}
+
+pub struct GroupSegmentCollector {
+    column: Option<StrColumn>,
+    segment: SegmentOrdinal,
+    size: usize,
+    groups: HashMap<Option<u64>, (usize, Vec<Hit>)>,
+}
+
+impl SegmentCollector for GroupSegmentCollector {
+    type Fruit = Vec<Group>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let ord = self
+            .column
+            .as_ref()
+            .and_then(|column| column.term_ords(doc).next());
+        let (count, hits) = self.groups.entry(ord).or_default();
+        *count += 1;
+        push_hit(hits, (score, DocAddress::new(self.segment, doc)), self.size);
+    }
+
+    fn harvest(self) -> Vec<Group> {
+        let mut key = String::new();
+        self.groups
+            .into_iter()
+            .map(|(ord, (count, mut hits))| {
+                key.clear();
+                if let (Some(column), Some(ord)) = (&self.column, ord) {
+                    // 序号来自同一段的词典，读取失败只可能是索引损坏，归到空组
+                    if column.ord_to_str(ord, &mut key).is_err() {
+                        key.clear();
+                    }
+                }
+                truncate(&mut hits, self.size);
+                Group {
+                    key: key.clone(),
+                    count,
+                    hits,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Collector for GroupCollector {
+    // 按组内最高得分排序的所有组
+    type Fruit = Vec<Group>;
+    type Child = GroupSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> tantivy::Result<GroupSegmentCollector> {
+        Ok(GroupSegmentCollector {
+            column: reader.fast_fields().str(&self.field)?,
+            segment,
+            size: self.size,
+            groups: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, fruits: Vec<Vec<Group>>) -> tantivy::Result<Vec<Group>> {
+        let mut merged: HashMap<String, Group> = HashMap::new();
+        for group in fruits.into_iter().flatten() {
+            match merged.get_mut(&group.key) {
+                Some(existing) => {
+                    existing.count += group.count;
+                    existing.hits.extend(group.hits);
+                    truncate(&mut existing.hits, self.size);
+                }
+                None => {
+                    merged.insert(group.key.clone(), group);
+                }
+            }
+        }
+        let mut groups: Vec<Group> = merged.into_values().collect();
+        groups.sort_by(|a, b| {
+            b.best()
+                .total_cmp(&a.best())
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        Ok(groups)
+    }
+}
@@ -0,0 +1,59 @@
+// 查询复杂度限制，在解析查询之前检查，避免超长查询和前导通配符拖慢搜索
+use crate::error::AppError;
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryLimits {
+    // 最大字符数
+    pub max_len: usize,
+    // 最多词项数（按空白分隔，不含 AND/OR/NOT）
+    pub max_terms: usize,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            max_len: 256,
+            max_terms: 32,
+        }
+    }
+}
+
+impl QueryLimits {
+    pub fn check(&self, query: &str) -> Result<()> {
+        let len = query.chars().count();
+        if len > self.max_len {
+            return Err(AppError::bad_request_msg(&format!(
+                "query is too long: {} characters, at most {} allowed",
+                len, self.max_len
+            )));
+        }
+
+        let mut terms = 0;
+        for token in query.split_whitespace() {
+            if matches!(token, "AND" | "OR" | "NOT") {
+                continue;
+            }
+            // 去掉 +/-、括号、引号和 field: 前缀后再看词项本身
+            let term = token.trim_start_matches(['+', '-', '(', '"']);
+            let term = term.split_once(':').map_or(term, |(_, value)| value);
+            let term = term.trim_start_matches(['(', '"']);
+            if term.starts_with(['*', '?']) {
+                return Err(AppError::bad_request_msg(&format!(
+                    "leading wildcards are not supported: {}",
+                    token
+                )));
+            }
+            if !term.trim_end_matches([')', '"']).is_empty() {
+                terms += 1;
+            }
+        }
+        if terms > self.max_terms {
+            return Err(AppError::bad_request_msg(&format!(
+                "query has too many terms: {}, at most {} allowed",
+                terms, self.max_terms
+            )));
+        }
+        Ok(())
+    }
+}
@@ -1 +1,28 @@
+pub mod bm25;
+pub mod collapse;
+pub mod correct;
+pub mod dedup;
 pub mod engine;
+pub mod group;
+pub mod limits;
+pub mod query;
+pub mod relevance;
+pub mod schema;
+pub mod service;
+pub mod suggest;
+pub mod synonyms;
+pub mod wrapped;
+
+pub use bm25::Bm25Params;
+pub use dedup::{Dedup, DedupMode, Duplicate, Fingerprint};
+pub use group::GroupBy;
+pub use limits::QueryLimits;
+pub use query::{ParsedQuery, QueryError};
+pub use relevance::{FieldBoosts, Relevance};
+pub use schema::FieldNormalizers;
+pub use service::{
+    DocSource, HitGroup, IndexDoc, IndexStats, SearchHit, SearchOptions, SearchResults,
+    SearchService, StoredDocs,
+};
+pub use suggest::{PopularThreshold, Suggestion};
+pub use synonyms::{SynonymSet, Synonyms};
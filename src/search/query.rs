@@ -0,0 +1,170 @@
+// 查询解析：严格模式下语法错误返回 400，宽松模式下忽略无法解析的部分并作为 warnings 返回
+use super::bm25::Bm25TermQuery;
+use super::wrapped::{BoostedQuery, FuzzyQuery, PrefixPhraseQuery};
+use crate::error::AppError;
+use crate::Result;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tantivy::query::{AllQuery, BooleanQuery, EmptyQuery, Occur, PhraseQuery, Query, QueryParser};
+use tantivy::query::{QueryParserError, TermQuery};
+use tantivy::query_grammar;
+use tantivy::schema::Schema;
+use tantivy::Term;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct QueryError {
+    // 出错位置（字符下标），无法定位时为 None
+    pub position: Option<usize>,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub struct ParsedQuery {
+    pub query: Box<dyn Query>,
+    // 宽松模式下被忽略的部分
+    pub warnings: Vec<QueryError>,
+}
+
+pub fn parse(parser: &QueryParser, keyword: &str, lenient: bool) -> Result<ParsedQuery> {
+    if lenient {
+        let (query, errors) = parser.parse_query_lenient(keyword);
+        // 语法错误重新用 query_grammar 解析一遍，拿到位置
+        let mut warnings = syntax_errors(keyword);
+        warnings.extend(
+            errors
+                .into_iter()
+                .filter(|err| !matches!(err, QueryParserError::SyntaxError(_)))
+                .map(|err| QueryError {
+                    position: None,
+                    reason: err.to_string(),
+                }),
+        );
+        return Ok(ParsedQuery { query, warnings });
+    }
+    match parser.parse_query(keyword) {
+        Ok(query) => Ok(ParsedQuery {
+            query,
+            warnings: Vec::new(),
+        }),
+        Err(err) => {
+            let mut errors = match err {
+                QueryParserError::SyntaxError(_) => syntax_errors(keyword),
+                _ => Vec::new(),
+            };
+            if errors.is_empty() {
+                errors.push(QueryError {
+                    position: None,
+                    reason: err.to_string(),
+                });
+            }
+            let message = match errors[0].position {
+                Some(position) => format!(
+                    "invalid query at position {}: {}",
+                    position, errors[0].reason
+                ),
+                None => format!("invalid query: {}", errors[0].reason),
+            };
+            Err(AppError::bad_request_msg(&message).with_details(json!(errors)))
+        }
+    }
+}
+
+fn syntax_errors(keyword: &str) -> Vec<QueryError> {
+    let (_, errors) = query_grammar::parse_query_lenient(keyword);
+    errors
+        .into_iter()
+        .map(|err| QueryError {
+            // LenientError 的位置是字节下标
+            position: keyword.get(..err.pos).map(|prefix| prefix.chars().count()),
+            reason: err.message,
+        })
+        .collect()
+}
+
+// 把解析后的查询转成 JSON 树，用于 /search/explain
+pub fn query_tree(query: &dyn Query, schema: &Schema) -> Value {
+    let term = |term: &Term| {
+        let value = term.value();
+        let text = match (value.as_str(), value.as_u64()) {
+            (Some(text), _) => json!(text),
+            (None, Some(n)) => json!(n),
+            _ => Value::Null,
+        };
+        json!({
+            "field": schema.get_field_name(term.field()),
+            "text": text,
+        })
+    };
+    if let Some(query) = query.downcast_ref::<BooleanQuery>() {
+        let clauses: Vec<Value> = query
+            .clauses()
+            .iter()
+            .map(|(occur, query)| {
+                json!({
+                    "occur": occur_name(*occur),
+                    "query": query_tree(query.as_ref(), schema),
+                })
+            })
+            .collect();
+        json!({ "type": "boolean", "clauses": clauses })
+    } else if let Some(query) = query.downcast_ref::<TermQuery>() {
+        let mut node = term(query.term());
+        node["type"] = json!("term");
+        node
+    } else if let Some(query) = query.downcast_ref::<Bm25TermQuery>() {
+        let mut node = term(query.term());
+        node["type"] = json!("term");
+        node["boost"] = json!(query.boost());
+        node
+    } else if let Some(query) = query.downcast_ref::<PhraseQuery>() {
+        let terms: Vec<Value> = query
+            .phrase_terms()
+            .iter()
+            .map(|t| term(t)["text"].clone())
+            .collect();
+        json!({
+            "type": "phrase",
+            "field": schema.get_field_name(query.field()),
+            "terms": terms,
+        })
+    } else if let Some(query) = query.downcast_ref::<PrefixPhraseQuery>() {
+        // 最后一个词是前缀
+        let terms: Vec<Value> = query
+            .terms()
+            .iter()
+            .map(|t| term(t)["text"].clone())
+            .collect();
+        json!({
+            "type": "phrase_prefix",
+            "field": schema.get_field_name(query.terms()[0].field()),
+            "terms": terms,
+        })
+    } else if let Some(query) = query.downcast_ref::<FuzzyQuery>() {
+        let mut node = term(query.term());
+        node["type"] = json!("fuzzy");
+        node["distance"] = json!(query.distance());
+        node["prefix"] = json!(query.is_prefix());
+        node
+    } else if let Some(query) = query.downcast_ref::<BoostedQuery>() {
+        json!({
+            "type": "boost",
+            "boost": query.boost(),
+            "query": query_tree(query.query(), schema),
+        })
+    } else if query.downcast_ref::<AllQuery>().is_some() {
+        json!({ "type": "all" })
+    } else if query.downcast_ref::<EmptyQuery>().is_some() {
+        json!({ "type": "empty" })
+    } else {
+        // 其他查询类型没有公开的访问方法，直接给出 Debug 输出
+        json!({ "type": "other", "debug": format!("{:?}", query) })
+    }
+}
+
+fn occur_name(occur: Occur) -> &'static str {
+    match occur {
+        Occur::Should => "should",
+        Occur::Must => "must",
+        Occur::MustNot => "must_not",
+    }
+}
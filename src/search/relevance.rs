@@ -0,0 +1,195 @@
+// 相关性参数：字段权重、默认 AND/OR 和 BM25 参数
+use super::bm25::{Bm25Params, Bm25TermQuery};
+use super::schema::SearchFields;
+use super::wrapped::{BoostedQuery, FuzzyQuery};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tantivy::query::{BooleanQuery, Occur, PhraseQuery, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Score, Term};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldBoosts {
+    pub title: Score,
+    pub body: Score,
+    pub url: Score,
+    // 拼音匹配，只在 pinyin=true 时使用
+    pub pinyin: Score,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        Self {
+            title: 2.0,
+            body: 1.0,
+            url: 0.5,
+            pinyin: 0.5,
+        }
+    }
+}
+
+impl FieldBoosts {
+    // 在当前权重上应用 `title^3 body^1` 形式的覆盖，没有提到的字段保持不变
+    pub fn apply(mut self, spec: &str) -> Result<Self, String> {
+        for part in spec.split([' ', ',']).filter(|s| !s.is_empty()) {
+            let (name, boost) = part
+                .split_once('^')
+                .ok_or_else(|| format!("expected `field^boost`, got `{}`", part))?;
+            let boost: Score = boost
+                .parse()
+                .ok()
+                .filter(|boost: &Score| boost.is_finite() && *boost >= 0.0)
+                .ok_or_else(|| format!("invalid boost for `{}`: `{}`", name, boost))?;
+            match name {
+                "title" => self.title = boost,
+                "body" => self.body = boost,
+                "url" => self.url = boost,
+                "pinyin" => self.pinyin = boost,
+                _ => return Err(format!("unknown field `{}`", name)),
+            }
+        }
+        Ok(self)
+    }
+
+    fn for_field(&self, fields: &SearchFields, field: Field) -> Option<Score> {
+        if field == fields.title {
+            Some(self.title)
+        } else if field == fields.body {
+            Some(self.body)
+        } else if field == fields.url {
+            Some(self.url)
+        } else if field == fields.pinyin {
+            Some(self.pinyin)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for FieldBoosts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::default().apply(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Relevance {
+    pub boosts: FieldBoosts,
+    // 多个词项默认用 AND 连接，默认为 OR
+    pub conjunction: bool,
+    pub bm25: Bm25Params,
+}
+
+// 模糊匹配和前缀匹配的得分打折，精确匹配排在前面
+const EXPANSION_WEIGHT: Score = 0.5;
+
+// 按字段权重和 BM25 参数改写 QueryParser 生成的查询：
+// 文本字段上的词项乘以字段权重，BM25 参数不是默认值时换成 Bm25TermQuery，
+// 可选地加上模糊匹配和前缀匹配；短语查询乘以字段权重
+pub(crate) struct Rewriter<'a> {
+    pub fields: &'a SearchFields,
+    pub boosts: FieldBoosts,
+    pub bm25: Bm25Params,
+    // 拉丁字母词项允许的最大编辑距离，0 表示不做模糊匹配
+    pub fuzzy: u8,
+    // 做前缀匹配的词项，即查询的最后一个词
+    pub prefixes: Vec<String>,
+    // 词项到原词的映射：索引中的英文词项是词干，模糊和前缀匹配要在原词字段上用原词展开
+    pub surfaces: HashMap<String, String>,
+}
+
+impl Rewriter<'_> {
+    pub fn rewrite(&self, query: Box<dyn Query>) -> Box<dyn Query> {
+        if let Some(boolean) = query.downcast_ref::<BooleanQuery>() {
+            let clauses = boolean
+                .clauses()
+                .iter()
+                .map(|(occur, query)| (*occur, self.rewrite(query.box_clone())))
+                .collect();
+            return Box::new(BooleanQuery::new(clauses));
+        }
+        if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+            let term = term_query.term();
+            if let Some(boost) = self.boosts.for_field(self.fields, term.field()) {
+                return self.term(term, boost);
+            }
+        }
+        if let Some(phrase) = query.downcast_ref::<PhraseQuery>() {
+            if let Some(boost) = self.boosts.for_field(self.fields, phrase.field()) {
+                let mut terms = phrase.phrase_terms();
+                // 正在输入的最后一个词做前缀匹配，其余词必须出现
+                if terms.last().is_some_and(|term| self.is_prefix(term)) {
+                    let last = terms.pop().unwrap();
+                    let mut clauses: Vec<(Occur, Box<dyn Query>)> = terms
+                        .into_iter()
+                        .map(|term| (Occur::Must, self.exact(term, boost)))
+                        .collect();
+                    clauses.push((Occur::Must, self.term(&last, boost)));
+                    return Box::new(BooleanQuery::new(clauses));
+                }
+                if boost != 1.0 {
+                    return Box::new(BoostedQuery::new(query, boost));
+                }
+            }
+        }
+        query
+    }
+
+    pub fn term(&self, term: &Term, boost: Score) -> Box<dyn Query> {
+        let exact = self.exact(term.clone(), boost);
+        let value = term.value();
+        let stem = value.as_str().unwrap_or_default();
+        let text = self.surfaces.get(stem).map_or(stem, String::as_str);
+        let raw = Term::from_field_text(self.fields.unstemmed(term.field()), text);
+        let mut expansions: Vec<Box<dyn Query>> = Vec::new();
+        if self.is_prefix(term) {
+            expansions.push(Box::new(FuzzyQuery::prefix(raw.clone(), 0)));
+        }
+        let distance = self.fuzzy.min(auto_distance(text));
+        if distance > 0 && text.chars().all(|c| c.is_ascii_alphabetic()) {
+            expansions.push(Box::new(FuzzyQuery::new(raw, distance)));
+        }
+        if expansions.is_empty() {
+            return exact;
+        }
+        let mut clauses = vec![(Occur::Should, exact)];
+        for expansion in expansions {
+            let query: Box<dyn Query> =
+                Box::new(BoostedQuery::new(expansion, boost * EXPANSION_WEIGHT));
+            clauses.push((Occur::Should, query));
+        }
+        Box::new(BooleanQuery::new(clauses))
+    }
+
+    // 默认参数下保留 tantivy 自带的 TermQuery，多个词项 OR 时可以用 block-max WAND 跳过低分文档
+    fn exact(&self, term: Term, boost: Score) -> Box<dyn Query> {
+        if self.bm25 != Bm25Params::default() {
+            return Box::new(Bm25TermQuery::new(term, self.bm25, boost));
+        }
+        let query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+        if boost == 1.0 {
+            query
+        } else {
+            Box::new(BoostedQuery::new(query, boost))
+        }
+    }
+
+    fn is_prefix(&self, term: &Term) -> bool {
+        let value = term.value();
+        let text = value.as_str().unwrap_or_default();
+        self.prefixes.iter().any(|prefix| prefix == text)
+    }
+}
+
+// 词越短允许的编辑距离越小，避免 "ab" 模糊匹配到大量无关的词
+pub(crate) fn auto_distance(text: &str) -> u8 {
+    match text.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
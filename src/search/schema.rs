@@ -0,0 +1,143 @@
+use crate::nlpcut::Normalizer;
+use serde_derive::Deserialize;
+use tantivy::schema::*;
+
+pub const JIEBA_TOKENIZER: &str = "jieba";
+pub const PINYIN_TOKENIZER: &str = "pinyin";
+// 各文本字段使用自己的分词器：按字段配置归一化后，标题、正文和关键词用中英文混排切词
+// （中文 jieba、英文词干），网址按主机名、路径和参数切词
+pub const TITLE_TOKENIZER: &str = "jieba_title";
+pub const BODY_TOKENIZER: &str = "jieba_body";
+pub const URL_TOKENIZER: &str = "jieba_url";
+pub const KEYWORDS_TOKENIZER: &str = "jieba_keywords";
+// 标题和正文不提取词干的副本，只转小写，模糊匹配和前缀匹配作用在原词上
+pub const TITLE_RAW_TOKENIZER: &str = "jieba_title_raw";
+pub const BODY_RAW_TOKENIZER: &str = "jieba_body_raw";
+// fast field 按名字读取
+pub const ID_FIELD: &str = "idstr";
+pub const SIMHASH_FIELD: &str = "simhash";
+pub const DUP_GROUP_FIELD: &str = "dup_group";
+pub const HOST_FIELD: &str = "host";
+pub const DOC_TYPE_FIELD: &str = "doc_type";
+pub const SOURCE_FIELD: &str = "source";
+
+// 字段、分词器或写入方式变化后加一，版本不一致的索引需要用 reindex 重建
+pub const SCHEMA_VERSION: u32 = 1;
+
+// 各字段的归一化选项，修改后需要重建索引
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldNormalizers {
+    pub title: Normalizer,
+    pub body: Normalizer,
+    pub url: Normalizer,
+    pub keywords: Normalizer,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SearchFields {
+    pub title: Field,
+    pub body: Field,
+    // 来源内的 id，/insert 写入的文档和数据库文档各自编号
+    pub id: Field,
+    // 文档来源（api 或 db），和 id 组成唯一的 key
+    pub source: Field,
+    pub key: Field,
+    pub url: Field,
+    // 标题和正文的拼音，只索引不存储
+    pub pinyin: Field,
+    // 写入时用 TF-IDF 提取的关键词，每个关键词一个值
+    pub keywords: Field,
+    // 标题和正文的 SimHash 指纹
+    pub simhash: Field,
+    // 指纹分段后的值，只索引，用于查找近似重复的候选文档
+    pub simhash_bands: Field,
+    // 近似重复文档的组号，不重复的文档是自己的 id
+    pub dup_group: Field,
+    // 网址的主机名和文档分类，不切词，用于过滤和分组
+    pub host: Field,
+    pub doc_type: Field,
+    // 标题和正文的原词，只索引不存储
+    pub title_raw: Field,
+    pub body_raw: Field,
+}
+
+impl SearchFields {
+    // 模糊匹配和前缀匹配使用的字段：标题和正文换成不提取词干的副本，其他字段本身就是原词
+    pub fn unstemmed(&self, field: Field) -> Field {
+        if field == self.title {
+            self.title_raw
+        } else if field == self.body {
+            self.body_raw
+        } else {
+            field
+        }
+    }
+}
+
+pub fn build_schema() -> (Schema, SearchFields) {
+    let mut schema_builder = Schema::builder();
+
+    let text_options = |tokenizer| {
+        TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(tokenizer)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+            .set_stored()
+    };
+
+    let title = schema_builder.add_text_field("title", text_options(TITLE_TOKENIZER));
+    let body = schema_builder.add_text_field("body", text_options(BODY_TOKENIZER));
+    let id = schema_builder.add_u64_field(ID_FIELD, INDEXED | STORED | FAST);
+    let source = schema_builder.add_text_field(SOURCE_FIELD, STRING | STORED | FAST);
+    let key = schema_builder.add_text_field("key", STRING);
+    let url = schema_builder.add_text_field("url", text_options(URL_TOKENIZER));
+    let pinyin = schema_builder.add_text_field(
+        "pinyin",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(PINYIN_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        ),
+    );
+
+    let keywords = schema_builder.add_text_field("keywords", text_options(KEYWORDS_TOKENIZER));
+    let simhash = schema_builder.add_u64_field(SIMHASH_FIELD, STORED | FAST);
+    let simhash_bands = schema_builder.add_u64_field("simhash_bands", INDEXED);
+    let dup_group = schema_builder.add_u64_field(DUP_GROUP_FIELD, STORED | FAST);
+    let host = schema_builder.add_text_field(HOST_FIELD, STRING | STORED | FAST);
+    let doc_type = schema_builder.add_text_field(DOC_TYPE_FIELD, STRING | STORED | FAST);
+    // 只用于词典上的模糊和前缀展开，不需要词频和位置
+    let raw_options = |tokenizer| {
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(tokenizer)
+                .set_index_option(IndexRecordOption::Basic),
+        )
+    };
+    let title_raw = schema_builder.add_text_field("title_raw", raw_options(TITLE_RAW_TOKENIZER));
+    let body_raw = schema_builder.add_text_field("body_raw", raw_options(BODY_RAW_TOKENIZER));
+
+    (
+        schema_builder.build(),
+        SearchFields {
+            title,
+            body,
+            id,
+            source,
+            key,
+            url,
+            pinyin,
+            keywords,
+            simhash,
+            simhash_bands,
+            dup_group,
+            host,
+            doc_type,
+            title_raw,
+            body_raw,
+        },
+    )
+}
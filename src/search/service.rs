@@ -0,0 +1,1262 @@
+use super::collapse::CollapseCollector;
+use super::correct;
+use super::dedup::{Dedup, DedupMode, Duplicate, Fingerprint};
+use super::group::{self, Group, GroupBy, GroupCollector};
+use super::limits::QueryLimits;
+use super::query::{self, ParsedQuery, QueryError};
+use super::relevance::{auto_distance, FieldBoosts, Relevance, Rewriter};
+use super::schema::{
+    build_schema, FieldNormalizers, SearchFields, BODY_RAW_TOKENIZER, BODY_TOKENIZER,
+    DOC_TYPE_FIELD, DUP_GROUP_FIELD, HOST_FIELD, ID_FIELD, JIEBA_TOKENIZER, KEYWORDS_TOKENIZER,
+    PINYIN_TOKENIZER, SCHEMA_VERSION, SIMHASH_FIELD, SOURCE_FIELD, TITLE_RAW_TOKENIZER,
+    TITLE_TOKENIZER, URL_TOKENIZER,
+};
+use super::suggest::{self, PopularQueries, PopularThreshold, Suggestion};
+use super::synonyms::{Synonyms, DEFAULT_SET};
+use super::wrapped::{BoostedQuery, FuzzyQuery, PrefixPhraseQuery};
+use crate::error::AppError;
+use crate::metrics;
+use crate::nlpcut::keywords::{self, Keyword, KeywordMethod};
+use crate::nlpcut::mixed;
+use crate::nlpcut::pinyin::{char_syllable, pinyin_syllables};
+use crate::nlpcut::simhash::{self, hamming};
+use crate::nlpcut::stopwords::STOPWORDS_CMN;
+use crate::nlpcut::url::host;
+use crate::nlpcut::{JiebaTokenizer, MixedTokenizer, PinyinTokenizer, UrlTokenizer};
+use crate::Result;
+use jieba_rs::Jieba;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
+use tantivy::directory::error::OpenReadError;
+use tantivy::directory::Directory;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{
+    BooleanQuery, Explanation, MoreLikeThisQuery, Occur, PhraseQuery, Query, QueryParser,
+    TermQuery, TermSetQuery,
+};
+use tantivy::schema::{Field, IndexRecordOption, Value};
+use tantivy::tokenizer::{LowerCaser, TextAnalyzer, Token, TokenStream};
+use tantivy::{
+    doc, DocAddress, Document, Index, IndexReader, IndexWriter, ReloadPolicy, Score, Searcher,
+    TantivyError, Term,
+};
+
+pub const DEFAULT_WRITER_MEMORY: usize = 50_000_000;
+// 索引目录中记录 schema 版本的文件，不归 tantivy 管理，不会被清理
+const SCHEMA_VERSION_FILE: &str = "schema_version";
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+// 补全时每个词前缀最多扫描的词典项数
+const MAX_SUGGEST_TERMS: usize = 1000;
+// 分组搜索时每组默认返回的文档数
+pub const DEFAULT_GROUP_SIZE: usize = 3;
+// 写入时为每个文档提取的关键词数
+const INDEX_KEYWORDS: usize = 5;
+// 相关文档查询最多使用的词数
+const MAX_SIMILAR_TERMS: usize = 25;
+
+// 文档来源：/insert 写入的文档用调用方给的 id，数据库文档用数据库 id，两者各自编号，
+// 索引中用来源和 id 组成的 key 区分，互不覆盖
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocSource {
+    #[default]
+    Api,
+    Db,
+}
+
+// 数据库文档的重复组号最高位为 1，和 /insert 的 id 区分
+const DB_GROUP_BIT: u64 = 1 << 63;
+
+impl DocSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DocSource::Api => "api",
+            DocSource::Db => "db",
+        }
+    }
+
+    fn parse(text: &str) -> Self {
+        if text == DocSource::Db.as_str() {
+            DocSource::Db
+        } else {
+            DocSource::Api
+        }
+    }
+
+    fn key(self, id: u64) -> String {
+        format!("{}:{}", self.as_str(), id)
+    }
+
+    fn group(self, id: u64) -> u64 {
+        match self {
+            DocSource::Api => id,
+            DocSource::Db => id | DB_GROUP_BIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IndexDoc {
+    pub id: u64,
+    // 不给出时是 /insert 写入的文档
+    #[serde(default)]
+    pub source: DocSource,
+    pub title: String,
+    pub body: String,
+    pub url: String,
+    // 文档分类，可以为空
+    #[serde(default)]
+    pub doc_type: String,
+}
+
+// 旧索引中存储的文档
+#[derive(Debug, Clone)]
+pub struct StoredDocs {
+    pub docs: Vec<IndexDoc>,
+    // 旧索引没有 source 字段时为 false，这时数据库文档也是按 /insert 的文档读出的
+    pub has_source: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IndexStats {
+    pub num_docs: u64,
+    pub num_segments: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    // 匹配的文档总数，不受 limit/offset 影响
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+    // 宽松模式下被忽略的查询片段
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<QueryError>,
+    // 没有结果时纠正后能搜到结果的查询
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    // auto_correct 时结果来自 suggestion
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub corrected: bool,
+    // group_by 时的分组结果，此时 hits 为空、total 是组数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<HitGroup>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HitGroup {
+    pub key: String,
+    // 组内匹配的文档数
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub limit: usize,
+    pub offset: usize,
+    // 使用 parse_query_lenient，查询中多余的引号、冒号等不会导致搜索失败
+    pub lenient: bool,
+    // 为 None 时使用 with_relevance 设置的默认值
+    pub boosts: Option<FieldBoosts>,
+    pub conjunction: Option<bool>,
+    // 为每个结果附上 Query::explain 的输出
+    pub explain: bool,
+    // 拉丁字母词项的最大编辑距离（0-2），0 表示不做模糊匹配
+    pub fuzzy: u8,
+    // 最后一个词做前缀匹配，用于边输入边搜索
+    pub prefix: bool,
+    // 同时按拼音匹配，"xiena" 可以搜到 "谢娜"
+    pub pinyin: bool,
+    // 没有结果时直接返回纠正后查询的结果
+    pub auto_correct: bool,
+    // 同义词词典名称，为 None 时使用 default 词典（如果有）
+    pub synonyms: Option<String>,
+    // 近似重复的文档只返回得分最高的一篇，total 是组数
+    pub collapse: bool,
+    // 按字段分组，limit/offset 作用于组，每组返回得分最高的 group_size 篇
+    pub group_by: Option<GroupBy>,
+    pub group_size: usize,
+    // 多样化：每个网站最多返回的文档数，total 是限制后的文档数
+    pub max_per_host: Option<usize>,
+    // 调用方标识（API key 或 IP），热门查询按不同客户端计数
+    pub client: Option<String>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: 10,
+            offset: 0,
+            lenient: false,
+            boosts: None,
+            conjunction: None,
+            explain: false,
+            fuzzy: 0,
+            prefix: false,
+            pinyin: false,
+            auto_correct: false,
+            synonyms: None,
+            collapse: false,
+            group_by: None,
+            group_size: DEFAULT_GROUP_SIZE,
+            max_per_host: None,
+            client: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: u64,
+    pub source: DocSource,
+    pub score: f32,
+    pub title: String,
+    pub body: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    // collapse=true 时同组被折叠掉的匹配文档数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicates: Option<usize>,
+    // explain=true 时给出得分的计算过程：词频、IDF、字段长度、权重
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Explanation>,
+}
+
+// 全文检索服务，不依赖 HTTP，可以直接嵌入其他程序
+#[derive(Clone)]
+pub struct SearchService {
+    index: Index,
+    reader: IndexReader,
+    fields: SearchFields,
+    jieba: Arc<Jieba>,
+    normalizers: FieldNormalizers,
+    writer_memory: usize,
+    query_limits: QueryLimits,
+    relevance: Relevance,
+    // 有结果的搜索词计数，用于 /suggest
+    popular: Arc<PopularQueries>,
+    synonyms: Arc<Synonyms>,
+    dedup: Dedup,
+    // 所有写入共用一个 IndexWriter，第一次写入时创建，shutdown 时提交并关闭
+    writer: Arc<Mutex<Option<IndexWriter>>>,
+    // 使用临时目录时持有它，drop 时目录被删除
+    _tempdir: Option<Arc<tempfile::TempDir>>,
+}
+
+impl SearchService {
+    pub fn create_in_ram() -> Result<Self> {
+        let (schema, fields) = build_schema();
+        Self::with_index(Index::create_in_ram(schema), fields, None)
+    }
+
+    pub fn create_in_tempdir() -> Result<Self> {
+        let (schema, fields) = build_schema();
+        let tempdir = tempfile::TempDir::new()?;
+        let index = Index::create_in_dir(tempdir.path(), schema)?;
+        Self::with_index(index, fields, Some(Arc::new(tempdir)))
+    }
+
+    // 已有索引的 schema 版本和当前版本不一致时返回错误，提示运行 reindex
+    pub fn open_or_create(path: &Path) -> Result<Self> {
+        let (schema, fields) = build_schema();
+        std::fs::create_dir_all(path)?;
+        let dir = MmapDirectory::open(path)?;
+        let exists = Index::exists(&dir).map_err(TantivyError::from)?;
+        if exists {
+            let version = schema_version(&dir)?;
+            if version != SCHEMA_VERSION {
+                return Err(AppError::unavailable_msg(&format!(
+                    "search index at {} has schema version {}, this build needs version {}; \
+                     rebuild it with `cargo run --release --example reindex -- <same options as the server>`",
+                    path.display(),
+                    version,
+                    SCHEMA_VERSION
+                )));
+            }
+        }
+        let index = Index::open_or_create(dir.clone(), schema)?;
+        if !exists {
+            dir.atomic_write(
+                Path::new(SCHEMA_VERSION_FILE),
+                SCHEMA_VERSION.to_string().as_bytes(),
+            )?;
+        }
+        Self::with_index(index, fields, None)
+    }
+
+    // 读出任意版本索引中存储的文档，用于按当前 schema 重建索引
+    pub fn stored_docs(path: &Path) -> Result<StoredDocs> {
+        let index = Index::open_in_dir(path)?;
+        let schema = index.schema();
+        let field = |name| schema.get_field(name).ok();
+        let (id, source) = (field(ID_FIELD), field(SOURCE_FIELD));
+        let (title, body, url, doc_type) = (
+            field("title"),
+            field("body"),
+            field("url"),
+            field(DOC_TYPE_FIELD),
+        );
+        let searcher = index.reader()?.searcher();
+        let mut docs = Vec::new();
+        for (segment_ord, segment) in searcher.segment_readers().iter().enumerate() {
+            for doc_id in segment.doc_ids_alive() {
+                let doc = searcher.doc(DocAddress::new(segment_ord as u32, doc_id))?;
+                let text = |field: Option<Field>| {
+                    field
+                        .and_then(|field| doc.get_first(field))
+                        .and_then(|value| value.as_text())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let Some(id) = id.and_then(|id| doc.get_first(id)).and_then(Value::as_u64) else {
+                    continue;
+                };
+                docs.push(IndexDoc {
+                    id,
+                    source: DocSource::parse(&text(source)),
+                    title: text(title),
+                    body: text(body),
+                    url: text(url),
+                    doc_type: text(doc_type),
+                });
+            }
+        }
+        Ok(StoredDocs {
+            docs,
+            has_source: source.is_some(),
+        })
+    }
+
+    fn with_index(
+        index: Index,
+        fields: SearchFields,
+        tempdir: Option<Arc<tempfile::TempDir>>,
+    ) -> Result<Self> {
+        // 每次提交后手动 reload，保证写入后立即可以搜索到
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let service = Self {
+            index,
+            reader,
+            fields,
+            jieba: Arc::new(Jieba::new()),
+            normalizers: FieldNormalizers::default(),
+            writer_memory: DEFAULT_WRITER_MEMORY,
+            query_limits: QueryLimits::default(),
+            relevance: Relevance::default(),
+            popular: Arc::new(PopularQueries::default()),
+            synonyms: Arc::new(Synonyms::default()),
+            dedup: Dedup::default(),
+            writer: Arc::new(Mutex::new(None)),
+            _tempdir: tempdir,
+        };
+        service.register_tokenizers();
+        Ok(service)
+    }
+
+    // 使用加载了用户词典的 Jieba 实例
+    pub fn with_jieba(mut self, jieba: Arc<Jieba>) -> Self {
+        self.jieba = jieba;
+        self.register_tokenizers();
+        self
+    }
+
+    pub fn with_writer_memory(mut self, writer_memory: usize) -> Self {
+        self.writer_memory = writer_memory;
+        self
+    }
+
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.query_limits = query_limits;
+        self
+    }
+
+    // 默认的字段权重、AND/OR 和 BM25 参数，单次搜索可以通过 SearchOptions 覆盖
+    pub fn with_relevance(mut self, relevance: Relevance) -> Self {
+        self.relevance = relevance;
+        self
+    }
+
+    // 热门查询进入补全的阈值，会清空已有的计数
+    pub fn with_popular(mut self, threshold: PopularThreshold) -> Self {
+        self.popular = Arc::new(PopularQueries::new(threshold));
+        self
+    }
+
+    pub fn with_synonyms(mut self, synonyms: Arc<Synonyms>) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    pub fn synonyms(&self) -> &Arc<Synonyms> {
+        &self.synonyms
+    }
+
+    // 近似重复检测的模式和汉明距离阈值
+    pub fn with_dedup(mut self, dedup: Dedup) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    pub fn dedup(&self) -> Dedup {
+        self.dedup
+    }
+
+    // 各字段的归一化选项，索引和查询使用同一套分词器；修改后需要重建索引
+    pub fn with_normalizers(mut self, normalizers: FieldNormalizers) -> Self {
+        self.normalizers = normalizers;
+        self.register_tokenizers();
+        self
+    }
+
+    fn register_tokenizers(&self) {
+        let tokenizers = self.index.tokenizers();
+        let jieba = JiebaTokenizer::new(self.jieba.clone());
+        let FieldNormalizers {
+            title,
+            body,
+            url,
+            keywords,
+        } = self.normalizers;
+        for (name, normalizer) in [
+            (TITLE_TOKENIZER, title),
+            (BODY_TOKENIZER, body),
+            (KEYWORDS_TOKENIZER, keywords),
+        ] {
+            let analyzer = TextAnalyzer::builder(MixedTokenizer::new(jieba.clone()))
+                .filter(normalizer)
+                .build();
+            tokenizers.register(name, analyzer);
+        }
+        let url_parts = TextAnalyzer::builder(MixedTokenizer::without_stemming(jieba.clone()))
+            .filter(url)
+            .build();
+        tokenizers.register(URL_TOKENIZER, UrlTokenizer::new(url_parts));
+        for (name, normalizer) in [(TITLE_RAW_TOKENIZER, title), (BODY_RAW_TOKENIZER, body)] {
+            let analyzer = TextAnalyzer::builder(MixedTokenizer::without_stemming(jieba.clone()))
+                .filter(normalizer)
+                .filter(LowerCaser)
+                .build();
+            tokenizers.register(name, analyzer);
+        }
+        tokenizers.register(JIEBA_TOKENIZER, jieba);
+        tokenizers.register(PINYIN_TOKENIZER, PinyinTokenizer);
+    }
+
+    pub fn jieba(&self) -> &Arc<Jieba> {
+        &self.jieba
+    }
+
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    pub fn fields(&self) -> SearchFields {
+        self.fields
+    }
+
+    fn with_writer<R>(&self, f: impl FnOnce(&mut IndexWriter) -> Result<R>) -> Result<R> {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let writer = match writer.as_mut() {
+            Some(writer) => writer,
+            None => writer.insert(self.index.writer(self.writer_memory)?),
+        };
+        f(writer)
+    }
+
+    #[tracing::instrument(name = "index.add", skip_all, fields(id = doc.id))]
+    pub fn add(&self, doc: &IndexDoc) -> Result<()> {
+        let fingerprint = self.fingerprint(&doc.title, &doc.body, Some((doc.source, doc.id)))?;
+        self.write_all([(doc, fingerprint)])
+    }
+
+    // 使用调用方已经算好的指纹写入，避免重复计算和查找
+    #[tracing::instrument(name = "index.add", skip_all, fields(id = doc.id))]
+    pub fn add_with(&self, doc: &IndexDoc, fingerprint: Fingerprint) -> Result<()> {
+        self.write_all([(doc, fingerprint)])
+    }
+
+    // 批量导入，所有文档只提交一次
+    #[tracing::instrument(name = "index.add_all", skip_all, fields(count = docs.len()))]
+    pub fn add_all(&self, docs: &[IndexDoc]) -> Result<()> {
+        let fingerprints = docs
+            .iter()
+            .map(|doc| self.fingerprint(&doc.title, &doc.body, Some((doc.source, doc.id))))
+            .collect::<Result<Vec<_>>>()?;
+        self.write_all(docs.iter().zip(fingerprints))
+    }
+
+    // 重复检测和写入在同一把写锁下完成，并发写入近似重复的文档时 reject 模式下只有一篇能写入。
+    // prepare 拿到指纹后返回要写入的文档，例如先写数据库拿到 id；重复被拒绝或 prepare 出错时不写入
+    #[tracing::instrument(name = "index.add", skip_all)]
+    pub fn add_checked(
+        &self,
+        title: &str,
+        body: &str,
+        exclude: Option<(DocSource, u64)>,
+        prepare: impl FnOnce(Fingerprint) -> Result<IndexDoc>,
+    ) -> Result<Fingerprint> {
+        self.with_writer(|index_writer| {
+            let fingerprint = self.fingerprint(title, body, exclude)?;
+            self.dedup.check(fingerprint.duplicate.as_ref())?;
+            let doc = prepare(fingerprint)?;
+            self.write_doc(index_writer, &doc, fingerprint)?;
+            self.commit(index_writer)?;
+            Ok(fingerprint)
+        })
+    }
+
+    // 批量版本，返回写入的文档数和找到的重复（文档 id、重复的文档）；reject 模式下跳过重复的文档。
+    // 只和已提交的文档比较，同一批里的重复不会被找到
+    #[tracing::instrument(name = "index.add_all", skip_all, fields(count = docs.len()))]
+    pub fn add_all_checked(&self, docs: &[IndexDoc]) -> Result<(usize, Vec<(u64, Duplicate)>)> {
+        self.with_writer(|index_writer| {
+            let mut count = 0;
+            let mut duplicates = Vec::new();
+            for doc in docs {
+                let fingerprint =
+                    self.fingerprint(&doc.title, &doc.body, Some((doc.source, doc.id)))?;
+                if let Some(duplicate) = fingerprint.duplicate {
+                    duplicates.push((doc.id, duplicate));
+                    if self.dedup.mode == DedupMode::Reject {
+                        continue;
+                    }
+                }
+                self.write_doc(index_writer, doc, fingerprint)?;
+                count += 1;
+            }
+            self.commit(index_writer)?;
+            Ok((count, duplicates))
+        })
+    }
+
+    // 指纹和重复查找在拿写锁之前完成
+    fn write_all<'a>(
+        &self,
+        docs: impl IntoIterator<Item = (&'a IndexDoc, Fingerprint)>,
+    ) -> Result<()> {
+        self.with_writer(|index_writer| {
+            for (doc, fingerprint) in docs {
+                self.write_doc(index_writer, doc, fingerprint)?;
+            }
+            self.commit(index_writer)
+        })
+    }
+
+    // 计算指纹并查找已提交文档中的近似重复，同一批里的重复不会被找到
+    pub fn fingerprint(
+        &self,
+        title: &str,
+        body: &str,
+        exclude: Option<(DocSource, u64)>,
+    ) -> Result<Fingerprint> {
+        let simhash = self.simhash(title, body);
+        Ok(Fingerprint {
+            simhash,
+            duplicate: self.find_duplicate(simhash, exclude)?,
+        })
+    }
+
+    pub fn simhash(&self, title: &str, body: &str) -> u64 {
+        simhash::simhash(&self.jieba, &format!("{}\n{}", title, body))
+    }
+
+    // 已提交的文档中指纹和 simhash 最接近、汉明距离不超过阈值的一篇，排除 exclude（来源和 id）；
+    // 没有开启重复检测时返回 None。指纹为 0 说明没有有效词，不参与比较。
+    // 先按指纹分段精确查找候选文档，只对候选计算汉明距离
+    pub fn find_duplicate(
+        &self,
+        simhash: u64,
+        exclude: Option<(DocSource, u64)>,
+    ) -> Result<Option<Duplicate>> {
+        if self.dedup.mode == DedupMode::Off || simhash == 0 {
+            return Ok(None);
+        }
+        let searcher = self.reader.searcher();
+        let bands = simhash::bands(simhash)
+            .map(|band| Term::from_field_u64(self.fields.simhash_bands, band))
+            .collect::<Vec<_>>();
+        let mut candidates: Vec<DocAddress> = searcher
+            .search(&TermSetQuery::new(bands), &DocSetCollector)?
+            .into_iter()
+            .collect();
+        candidates.sort();
+        let mut nearest: Option<Duplicate> = None;
+        for segment_docs in candidates.chunk_by(|a, b| a.segment_ord == b.segment_ord) {
+            let segment = searcher.segment_reader(segment_docs[0].segment_ord);
+            let fast_fields = segment.fast_fields();
+            let ids = fast_fields.u64(ID_FIELD)?;
+            let simhashes = fast_fields.u64(SIMHASH_FIELD)?;
+            let groups = fast_fields.u64(DUP_GROUP_FIELD)?;
+            let sources = fast_fields.str(SOURCE_FIELD)?;
+            let db_ord = match &sources {
+                Some(sources) => sources.dictionary().term_ord(DocSource::Db.as_str())?,
+                None => None,
+            };
+            for &DocAddress { doc_id: doc, .. } in segment_docs {
+                let (Some(id), Some(other)) = (ids.first(doc), simhashes.first(doc)) else {
+                    continue;
+                };
+                let source = match (&sources, db_ord) {
+                    (Some(sources), Some(db_ord))
+                        if sources.term_ords(doc).next() == Some(db_ord) =>
+                    {
+                        DocSource::Db
+                    }
+                    _ => DocSource::Api,
+                };
+                let distance = hamming(simhash, other);
+                if other == 0
+                    || distance > self.dedup.threshold
+                    || Some((source, id)) == exclude
+                    || nearest.is_some_and(|nearest| nearest.distance <= distance)
+                {
+                    continue;
+                }
+                nearest = Some(Duplicate {
+                    id,
+                    source,
+                    group: groups.first(doc).unwrap_or(source.group(id)),
+                    distance,
+                });
+            }
+        }
+        Ok(nearest)
+    }
+
+    fn write_doc(
+        &self,
+        index_writer: &mut IndexWriter,
+        doc: &IndexDoc,
+        fingerprint: Fingerprint,
+    ) -> Result<()> {
+        let SearchFields {
+            title,
+            body,
+            id,
+            source,
+            key,
+            url,
+            pinyin,
+            keywords,
+            simhash,
+            simhash_bands,
+            dup_group,
+            host: host_field,
+            doc_type,
+            title_raw,
+            body_raw,
+        } = self.fields;
+        // 相同来源和 id 的旧文档先删除，保证 key 唯一
+        let doc_key = doc.source.key(doc.id);
+        index_writer.delete_term(Term::from_field_text(key, &doc_key));
+        let mut document = doc!(
+            title => doc.title.as_str(),
+            title_raw => doc.title.as_str(),
+            id => doc.id,
+            source => doc.source.as_str(),
+            key => doc_key,
+            body => doc.body.as_str(),
+            body_raw => doc.body.as_str(),
+            url => doc.url.as_str(),
+            pinyin => doc.title.as_str(),
+            pinyin => doc.body.as_str(),
+            host_field => host(&doc.url),
+            doc_type => doc.doc_type.as_str(),
+        );
+        // IDF 来自已经提交的文档，批量导入时不包含同一批的文档
+        let text = format!("{}\n{}", doc.title, doc.body);
+        for keyword in self.keywords(&text, INDEX_KEYWORDS, KeywordMethod::TfIdf)? {
+            document.add_text(keywords, &keyword.word);
+        }
+        let group = match (self.dedup.mode, fingerprint.duplicate) {
+            (DedupMode::Group, Some(duplicate)) => duplicate.group,
+            _ => doc.source.group(doc.id),
+        };
+        document.add_u64(simhash, fingerprint.simhash);
+        if fingerprint.simhash != 0 {
+            for band in simhash::bands(fingerprint.simhash) {
+                document.add_u64(simhash_bands, band);
+            }
+        }
+        document.add_u64(dup_group, group);
+        index_writer.add_document(document)?;
+        Ok(())
+    }
+
+    pub fn search(&self, keyword: &str, limit: usize, offset: usize) -> Result<SearchResults> {
+        self.search_with(
+            keyword,
+            &SearchOptions {
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tracing::instrument(name = "search", skip(self), fields(total))]
+    pub fn search_with(&self, keyword: &str, options: &SearchOptions) -> Result<SearchResults> {
+        let ParsedQuery { query, warnings } = self.parse_query(keyword, options)?;
+        let searcher = self.reader.searcher();
+        let mut groups = None;
+        let (top_docs, total) = if let Some(group_by) = options.group_by {
+            let collector = GroupCollector::new(group_by.field(), options.group_size.max(1));
+            let all = searcher.search(&query, &collector)?;
+            let total = all.len();
+            groups = Some(
+                all.into_iter()
+                    .skip(options.offset)
+                    .take(options.limit)
+                    .collect::<Vec<Group>>(),
+            );
+            (Vec::new(), total)
+        } else if let Some(cap) = options.max_per_host {
+            let collector = GroupCollector::new(HOST_FIELD, cap.max(1));
+            let capped = group::flatten(searcher.search(&query, &collector)?);
+            let total = capped.len();
+            let top_docs = capped
+                .into_iter()
+                .skip(options.offset)
+                .take(options.limit)
+                .map(|(score, address)| (score, address, None))
+                .collect();
+            (top_docs, total)
+        } else if options.collapse {
+            let collapse = CollapseCollector::new(DUP_GROUP_FIELD, options.limit, options.offset);
+            let (top_docs, groups) = searcher.search(&query, &collapse)?;
+            let top_docs = top_docs
+                .into_iter()
+                .map(|(score, address, duplicates)| (score, address, Some(duplicates)))
+                .collect();
+            (top_docs, groups)
+        } else {
+            let collector = (
+                TopDocs::with_limit(options.limit).and_offset(options.offset),
+                Count,
+            );
+            let (top_docs, total) = searcher.search(&query, &collector)?;
+            let top_docs = top_docs
+                .into_iter()
+                .map(|(score, address)| (score, address, None))
+                .collect::<Vec<_>>();
+            (top_docs, total)
+        };
+        metrics::SEARCH_HITS.observe(total as f64);
+        tracing::Span::current().record("total", total);
+        // 前缀搜索是输入到一半的查询，不计入热门查询
+        if total > 0 && !options.prefix {
+            self.popular.record(keyword, options.client.as_deref());
+        }
+        let hit = |score: Score, doc_address: DocAddress| -> Result<SearchHit> {
+            let retrieved_doc = searcher.doc(doc_address)?;
+            let mut hit = self.to_hit(score, &retrieved_doc);
+            if options.explain {
+                hit.explanation = Some(query.explain(&searcher, doc_address)?);
+            }
+            Ok(hit)
+        };
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, doc_address, duplicates) in top_docs {
+            let mut hit = hit(score, doc_address)?;
+            hit.duplicates = duplicates;
+            hits.push(hit);
+        }
+        let groups = groups
+            .map(|groups| {
+                groups
+                    .into_iter()
+                    .map(|group| {
+                        Ok(HitGroup {
+                            key: group.key,
+                            total: group.count,
+                            hits: group
+                                .hits
+                                .into_iter()
+                                .map(|(score, address)| hit(score, address))
+                                .collect::<Result<_>>()?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let mut results = SearchResults {
+            total,
+            hits,
+            warnings,
+            suggestion: None,
+            corrected: false,
+            groups,
+        };
+        if total == 0 {
+            results.suggestion = self.suggest_correction(keyword, options)?;
+        }
+        if let (Some(suggestion), true) = (&results.suggestion, options.auto_correct) {
+            let retry = SearchOptions {
+                auto_correct: false,
+                ..options.clone()
+            };
+            let mut corrected = self.search_with(suggestion, &retry)?;
+            corrected.suggestion = results.suggestion;
+            corrected.corrected = true;
+            return Ok(corrected);
+        }
+        Ok(results)
+    }
+
+    // 纠正后的查询能搜到结果时才作为建议
+    fn suggest_correction(&self, keyword: &str, options: &SearchOptions) -> Result<Option<String>> {
+        let Some(corrected) = self.correct(keyword)? else {
+            return Ok(None);
+        };
+        let Ok(parsed) = self.parse_query(&corrected, options) else {
+            return Ok(None);
+        };
+        let total = self.reader.searcher().search(&parsed.query, &Count)?;
+        Ok((total > 0).then_some(corrected))
+    }
+
+    // 逐词纠错，没有需要纠正的词时返回 None；带查询语法的词原样保留
+    pub fn correct(&self, keyword: &str) -> Result<Option<String>> {
+        let searcher = self.reader.searcher();
+        let mut changed = false;
+        let mut words = Vec::new();
+        for word in keyword.split_whitespace() {
+            let corrected = if correct::is_plain_word(word) {
+                self.correct_word(&searcher, word)?
+            } else {
+                None
+            };
+            changed |= corrected.is_some();
+            words.push(corrected.unwrap_or_else(|| word.to_string()));
+        }
+        Ok(changed.then(|| words.join(" ")))
+    }
+
+    // 英文词先按编辑距离纠正，找不到时和中文词一样按拼音找同音词
+    fn correct_word(&self, searcher: &Searcher, word: &str) -> Result<Option<String>> {
+        let word = &self.normalizers.title.normalize(word);
+        let fields = [self.fields.title, self.fields.body];
+        let latin = word.chars().all(|c| c.is_ascii_alphabetic());
+        if latin {
+            // 索引中的英文词项是词干，纠正结果换回文档中的原词
+            let term = mixed::stem(word);
+            if correct::term_exists(searcher, &fields, &term)? {
+                return Ok(None);
+            }
+            let distance = usize::from(auto_distance(word));
+            if let Some(term) = correct::nearest_term(searcher, &fields, &term, distance)? {
+                return Ok(Some(correct::surface_form(searcher, &self.fields, &term)?));
+            }
+        } else if !word.chars().all(|c| char_syllable(c).is_some()) {
+            return Ok(None);
+        }
+        let (syllables, partial) = pinyin_syllables(word);
+        // 单个音节的同音字太多，不做纠正
+        if partial.is_some() || syllables.len() < 2 {
+            return Ok(None);
+        }
+        correct::homophone(searcher, &self.fields, word, &syllables)
+    }
+
+    // 检查查询限制并做同义词扩展后解析，严格模式下语法错误返回带位置的 400；
+    // 解析结果按字段权重、BM25 参数以及模糊/前缀/拼音选项改写
+    pub fn parse_query(&self, keyword: &str, options: &SearchOptions) -> Result<ParsedQuery> {
+        self.query_limits.check(keyword)?;
+        if options.fuzzy > MAX_FUZZY_DISTANCE {
+            return Err(AppError::bad_request_msg(&format!(
+                "fuzzy distance must be at most {}",
+                MAX_FUZZY_DISTANCE
+            )));
+        }
+        let SearchFields {
+            title, body, url, ..
+        } = self.fields;
+        let mut query_parser = QueryParser::for_index(&self.index, vec![title, body, url]);
+        if options.conjunction.unwrap_or(self.relevance.conjunction) {
+            query_parser.set_conjunction_by_default();
+        }
+        let expanded = self.expand_synonyms(keyword, options.synonyms.as_deref())?;
+        let parsed = query::parse(&query_parser, &expanded, options.lenient)?;
+
+        let mut prefixes = Vec::new();
+        if options.prefix {
+            if let Some(last) = self.last_token(keyword) {
+                prefixes.push(last);
+            }
+        }
+        let surfaces = if options.fuzzy > 0 || options.prefix {
+            self.surface_words(&expanded)
+        } else {
+            HashMap::new()
+        };
+        let rewriter = Rewriter {
+            fields: &self.fields,
+            boosts: options.boosts.unwrap_or(self.relevance.boosts),
+            bm25: self.relevance.bm25,
+            fuzzy: options.fuzzy,
+            prefixes,
+            surfaces,
+        };
+        let mut query = rewriter.rewrite(parsed.query);
+        if options.pinyin {
+            query = Box::new(BooleanQuery::new(vec![
+                (Occur::Should, query),
+                (
+                    Occur::Should,
+                    self.pinyin_query(keyword, rewriter.boosts.pinyin, options.prefix),
+                ),
+            ]));
+        }
+        Ok(ParsedQuery {
+            query,
+            warnings: parsed.warnings,
+        })
+    }
+
+    fn expand_synonyms(&self, keyword: &str, name: Option<&str>) -> Result<String> {
+        match (self.synonyms.get(name.unwrap_or(DEFAULT_SET)), name) {
+            (Some(set), _) => Ok(set.expand(&self.jieba, keyword)),
+            (None, Some(name)) => Err(AppError::bad_request_msg(&format!(
+                "unknown synonym set `{}`",
+                name
+            ))),
+            (None, None) => Ok(keyword.to_string()),
+        }
+    }
+
+    // 查询中的词项（英文是词干）到原词的映射，模糊和前缀匹配用原词展开
+    fn surface_words(&self, keyword: &str) -> HashMap<String, String> {
+        let tokenizers = self.index.tokenizers();
+        let (Some(mut stemmed), Some(mut raw)) = (
+            tokenizers.get(TITLE_TOKENIZER),
+            tokenizers.get(TITLE_RAW_TOKENIZER),
+        ) else {
+            return HashMap::new();
+        };
+        let mut words = HashMap::new();
+        let mut stream = stemmed.token_stream(keyword);
+        while let Some(token) = stream.next() {
+            let Some(text) = keyword.get(token.offset_from..token.offset_to) else {
+                continue;
+            };
+            if let Some(word) = raw.token_stream(text).next() {
+                words
+                    .entry(token.text.clone())
+                    .or_insert_with(|| word.text.clone());
+            }
+        }
+        words
+    }
+
+    // 查询中最后一个词（正在输入的词），jieba 搜索模式下取结尾处最短的词
+    fn last_token(&self, keyword: &str) -> Option<String> {
+        let chunk = keyword.split_whitespace().last()?;
+        let mut analyzer = self.index.tokenizers().get(TITLE_TOKENIZER)?;
+        let mut stream = analyzer.token_stream(chunk);
+        let mut last: Option<Token> = None;
+        while let Some(token) = stream.next() {
+            if last
+                .as_ref()
+                .is_none_or(|last| token.offset_to >= last.offset_to)
+            {
+                last = Some(token.clone());
+            }
+        }
+        last.map(|token| token.text)
+    }
+
+    // 每个词转成拼音音节序列按短语匹配，任一词匹配即可；排除的词（-xxx）不参与。
+    // 前缀模式下最后一个词的最后一个音节可以不完整
+    fn pinyin_query(&self, keyword: &str, boost: Score, prefix: bool) -> Box<dyn Query> {
+        let words: Vec<&str> = keyword
+            .split_whitespace()
+            .filter(|word| !word.starts_with('-') && !matches!(*word, "AND" | "OR" | "NOT"))
+            .collect();
+        let field = self.fields.pinyin;
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            let is_last = prefix && i + 1 == words.len();
+            let (mut syllables, partial) = pinyin_syllables(word);
+            match partial {
+                Some(partial) if is_last => syllables.push(partial),
+                Some(_) => continue,
+                None => {}
+            }
+            let mut terms: Vec<Term> = syllables
+                .iter()
+                .map(|syllable| Term::from_field_text(field, syllable))
+                .collect();
+            let query: Box<dyn Query> = match (terms.len(), is_last) {
+                (0, _) => continue,
+                (1, true) => Box::new(FuzzyQuery::prefix(terms.remove(0), 0)),
+                (1, false) => Box::new(TermQuery::new(
+                    terms.remove(0),
+                    IndexRecordOption::WithFreqs,
+                )),
+                (_, true) => Box::new(PrefixPhraseQuery::new(terms)),
+                (_, false) => Box::new(PhraseQuery::new(terms)),
+            };
+            clauses.push((Occur::Should, Box::new(BoostedQuery::new(query, boost))));
+        }
+        Box::new(BooleanQuery::new(clauses))
+    }
+
+    // 补全输入中的最后一个词：整段输入和 jieba 切分后的最后一个词分别在标题词典中按前缀查找，
+    // 再加上以输入开头的热门查询，按频次排序
+    #[tracing::instrument(name = "suggest", skip(self))]
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<Suggestion>> {
+        let prefix = self
+            .normalizers
+            .title
+            .normalize(&suggest::normalize(prefix));
+        if prefix.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (before, word) = match prefix.rsplit_once(' ') {
+            Some((before, word)) => (format!("{} ", before), word),
+            None => (String::new(), prefix.as_str()),
+        };
+        let mut stems = vec![(before.clone(), word.to_string())];
+        // 输入完整的英文词时也按词干查找
+        if word.chars().all(|c| c.is_ascii_alphabetic()) {
+            let word_stem = mixed::stem(word);
+            if !word_stem.is_empty() && word_stem != word {
+                stems.push((before.clone(), word_stem));
+            }
+        }
+        let mut words = self.jieba.cut(word, true);
+        if let Some(last) = words.pop() {
+            if !words.is_empty() {
+                stems.push((format!("{}{}", before, words.concat()), last.to_string()));
+            }
+        }
+
+        let mut weights: HashMap<String, u64> = HashMap::new();
+        let searcher = self.reader.searcher();
+        for (head, stem) in stems {
+            // 英文词项是词干，排在前面的换成文档中的原词
+            for Suggestion { text, weight } in suggest::rank(self.title_terms(&stem)?, limit) {
+                let text = correct::surface_form(&searcher, &self.fields, &text)?;
+                let entry = weights.entry(format!("{}{}", head, text)).or_default();
+                *entry = (*entry).max(weight);
+            }
+        }
+        for (query, count) in self.popular.completions(&prefix) {
+            *weights.entry(query).or_default() += count;
+        }
+        // 和输入完全相同的不算补全
+        weights.remove(&prefix);
+        Ok(suggest::rank(weights, limit))
+    }
+
+    // 标题词典中以 stem 开头的词项和包含它的文档数，英文词项已经转成小写词干
+    fn title_terms(&self, stem: &str) -> Result<HashMap<String, u64>> {
+        let mut terms: HashMap<String, u64> = HashMap::new();
+        let searcher = self.reader.searcher();
+        for segment in searcher.segment_readers() {
+            let inverted_index = segment.inverted_index(self.fields.title)?;
+            let mut stream = inverted_index
+                .terms()
+                .range()
+                .ge(stem.as_bytes())
+                .into_stream()?;
+            let mut scanned = 0;
+            while scanned < MAX_SUGGEST_TERMS && stream.advance() {
+                if !stream.key().starts_with(stem.as_bytes()) {
+                    break;
+                }
+                scanned += 1;
+                if let Ok(term) = std::str::from_utf8(stream.key()) {
+                    *terms.entry(term.to_string()).or_default() +=
+                        u64::from(stream.value().doc_freq);
+                }
+            }
+        }
+        Ok(terms)
+    }
+
+    // 按索引中 id 对应文档的标题和正文构造 MoreLikeThisQuery，排除文档本身；
+    // 文档不存在时返回 None
+    #[tracing::instrument(name = "similar", skip(self))]
+    pub fn similar(
+        &self,
+        source: DocSource,
+        id: u64,
+        limit: usize,
+    ) -> Result<Option<Vec<SearchHit>>> {
+        let searcher = self.reader.searcher();
+        let id_term = Term::from_field_text(self.fields.key, &source.key(id));
+        let source = TermQuery::new(id_term.clone(), IndexRecordOption::Basic);
+        let Some((_, address)) = searcher
+            .search(&source, &TopDocs::with_limit(1))?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+        let doc = searcher.doc(address)?;
+        let values = |field| doc.get_all(field).cloned().collect::<Vec<Value>>();
+        let more_like_this = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_max_query_terms(MAX_SIMILAR_TERMS)
+            // 按字节计算，过滤掉空格和英文标点，汉字不受影响
+            .with_min_word_length(2)
+            .with_boost_factor(1.0)
+            .with_stop_words(STOPWORDS_CMN.iter().map(|word| word.to_string()).collect())
+            .with_document_fields(vec![
+                (self.fields.title, values(self.fields.title)),
+                (self.fields.body, values(self.fields.body)),
+            ]);
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(more_like_this) as Box<dyn Query>),
+            (
+                Occur::MustNot,
+                Box::new(TermQuery::new(id_term, IndexRecordOption::Basic)),
+            ),
+        ]);
+        let mut hits = Vec::new();
+        for (score, address) in searcher.search(&query, &TopDocs::with_limit(limit))? {
+            hits.push(self.to_hit(score, &searcher.doc(address)?));
+        }
+        Ok(Some(hits))
+    }
+
+    pub fn keywords(
+        &self,
+        text: &str,
+        top_k: usize,
+        method: KeywordMethod,
+    ) -> Result<Vec<Keyword>> {
+        let text = self.normalizers.body.normalize(text);
+        let words = keywords::candidates(&self.jieba, &text);
+        match method {
+            KeywordMethod::TfIdf => {
+                let idf = self.idf_table(&words)?;
+                Ok(keywords::tfidf(&words, top_k, |word| idf[word]))
+            }
+            KeywordMethod::TextRank => Ok(keywords::textrank(&words, top_k)),
+        }
+    }
+
+    // 按标题和正文中较大的文档频率计算平滑后的 IDF：ln((N + 1) / (df + 1)) + 1
+    fn idf_table(&self, words: &[&str]) -> Result<HashMap<String, f64>> {
+        let searcher = self.reader.searcher();
+        let num_docs = searcher.num_docs() as f64;
+        let mut idf = HashMap::new();
+        for &word in words {
+            if idf.contains_key(word) {
+                continue;
+            }
+            let mut doc_freq = 0;
+            for field in [self.fields.title, self.fields.body] {
+                doc_freq = doc_freq.max(searcher.doc_freq(&Term::from_field_text(field, word))?);
+            }
+            let value = ((num_docs + 1.0) / (doc_freq as f64 + 1.0)).ln() + 1.0;
+            idf.insert(word.to_string(), value);
+        }
+        Ok(idf)
+    }
+
+    pub fn relevance(&self) -> Relevance {
+        self.relevance
+    }
+
+    // 删除 /insert 写入的文档
+    #[tracing::instrument(name = "index.delete", skip(self))]
+    pub fn delete(&self, id: u64) -> Result<()> {
+        self.delete_from(DocSource::Api, id)
+    }
+
+    pub fn delete_from(&self, source: DocSource, id: u64) -> Result<()> {
+        self.with_writer(|index_writer| {
+            let key = Term::from_field_text(self.fields.key, &source.key(id));
+            index_writer.delete_term(key);
+            self.commit(index_writer)
+        })
+    }
+
+    #[tracing::instrument(name = "index.commit", skip_all)]
+    fn commit(&self, index_writer: &mut IndexWriter) -> Result<()> {
+        let start = Instant::now();
+        index_writer.commit()?;
+        metrics::INDEX_COMMIT_SECONDS.observe(start.elapsed().as_secs_f64());
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    // 文档数和段数，用于 /metrics
+    pub fn stats(&self) -> IndexStats {
+        let searcher = self.reader.searcher();
+        IndexStats {
+            num_docs: searcher.num_docs(),
+            num_segments: searcher.segment_readers().len(),
+        }
+    }
+
+    // 重新加载 reader，用于 /readyz 检查索引是否可读
+    pub fn check_reader(&self) -> Result<()> {
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    // 提交未提交的文档，等待合并线程结束后释放 IndexWriter
+    pub fn shutdown(&self) -> Result<()> {
+        let writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(mut writer) = writer {
+            self.commit(&mut writer)?;
+            writer.wait_merging_threads()?;
+        }
+        Ok(())
+    }
+
+    fn to_hit(&self, score: f32, doc: &Document) -> SearchHit {
+        let text = |field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_text())
+                .unwrap_or_default()
+                .to_string()
+        };
+        SearchHit {
+            id: doc
+                .get_first(self.fields.id)
+                .and_then(|v| v.as_u64())
+                .unwrap_or_default(),
+            source: DocSource::parse(&text(self.fields.source)),
+            score,
+            title: text(self.fields.title),
+            body: text(self.fields.body),
+            url: text(self.fields.url),
+            keywords: doc
+                .get_all(self.fields.keywords)
+                .filter_map(|v| v.as_text())
+                .map(str::to_string)
+                .collect(),
+            duplicates: None,
+            explanation: None,
+        }
+    }
+}
+
+// 没有版本文件的索引是加上版本号之前创建的，算作 0
+fn schema_version(dir: &MmapDirectory) -> Result<u32> {
+    match dir.atomic_read(Path::new(SCHEMA_VERSION_FILE)) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).trim().parse().map_err(|_| {
+            AppError::unavailable_msg("invalid schema_version file in the search index")
+        }),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(0),
+        Err(err) => Err(TantivyError::from(err).into()),
+    }
+}
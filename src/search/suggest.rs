@@ -0,0 +1,117 @@
+// 搜索框自动补全：标题词项来自索引的词典（tantivy 的词典本身就是 FST），
+// 热门查询在内存中计数，两者按频次合并排序
+use serde_derive::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, PoisonError};
+
+// 超过这个数量时丢弃只出现过一次的查询，避免内存无限增长
+const MAX_POPULAR_QUERIES: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub text: String,
+    // 标题中包含该词的文档数加上查询次数
+    pub weight: u64,
+}
+
+// 合并空白，英文转小写，作为查询计数的键
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// 查询要被搜索足够多次、来自足够多的客户端才会出现在补全里，
+// 避免单个用户搜一次就把任意内容推给所有人
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopularThreshold {
+    pub min_count: u64,
+    pub min_clients: usize,
+}
+
+impl Default for PopularThreshold {
+    fn default() -> Self {
+        Self {
+            min_count: 3,
+            min_clients: 1,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Popular {
+    count: u64,
+    // 客户端标识的哈希，最多记 min_clients 个，够了就不再增加
+    clients: HashSet<u64>,
+}
+
+#[derive(Default)]
+pub struct PopularQueries {
+    threshold: PopularThreshold,
+    counts: Mutex<HashMap<String, Popular>>,
+}
+
+impl PopularQueries {
+    pub fn new(threshold: PopularThreshold) -> Self {
+        Self {
+            threshold,
+            counts: Mutex::default(),
+        }
+    }
+
+    // client 为 None 时都算作同一个客户端
+    pub fn record(&self, query: &str, client: Option<&str>) {
+        let query = normalize(query);
+        if query.is_empty() {
+            return;
+        }
+        let mut counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+        if counts.len() >= MAX_POPULAR_QUERIES && !counts.contains_key(&query) {
+            counts.retain(|_, popular| popular.count > 1);
+            if counts.len() >= MAX_POPULAR_QUERIES {
+                return;
+            }
+        }
+        let popular = counts.entry(query).or_default();
+        popular.count += 1;
+        if popular.clients.len() < self.threshold.min_clients {
+            let mut hasher = DefaultHasher::new();
+            client.unwrap_or_default().hash(&mut hasher);
+            popular.clients.insert(hasher.finish());
+        }
+    }
+
+    // 以 prefix 开头、达到阈值的查询及其次数，prefix 需要先 normalize
+    pub fn completions(&self, prefix: &str) -> Vec<(String, u64)> {
+        let counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+        counts
+            .iter()
+            .filter(|(query, popular)| {
+                query.starts_with(prefix)
+                    && popular.count >= self.threshold.min_count
+                    && popular.clients.len() >= self.threshold.min_clients
+            })
+            .map(|(query, popular)| (query.clone(), popular.count))
+            .collect()
+    }
+}
+
+// 按权重从高到低排序，权重相同时短的在前
+pub fn rank(weights: HashMap<String, u64>, limit: usize) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = weights
+        .into_iter()
+        .map(|(text, weight)| Suggestion { text, weight })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.weight
+            .cmp(&a.weight)
+            .then_with(|| a.text.chars().count().cmp(&b.text.chars().count()))
+            .then_with(|| a.text.cmp(&b.text))
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
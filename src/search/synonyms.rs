@@ -0,0 +1,208 @@
+// 查询时的同义词扩展，词典使用 Solr 的格式：
+//   北京大学, 北大          等价的词，任一个都扩展成整组
+//   帝都, 京城 => 北京      左边的词替换成右边的词
+// 查询先按空白分词再用 jieba 切分，命中词典的词改写成 (北京大学 OR 北大) 后交给 QueryParser。
+// 可以配置多个命名的词典，搜索时用 synonyms=名称 选择，没有指定时使用 default
+use super::correct::is_plain_word;
+use crate::nlpcut::stopwords::STOPWORDS_CMN;
+use jieba_rs::Jieba;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+
+pub const DEFAULT_SET: &str = "default";
+// 查询中连续几个 jieba 词拼起来匹配词典，例如 "北京 大学" 被切开时也能匹配 "北京大学"
+const MAX_JOINED_TOKENS: usize = 4;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SynonymSet {
+    // 词 -> 扩展后的词组
+    groups: HashMap<String, Vec<String>>,
+}
+
+impl SynonymSet {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut set = Self::default();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words = |part: &str| -> Result<Vec<String>, String> {
+                let words: Vec<String> = part
+                    .split(',')
+                    .map(|word| word.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|word| !word.is_empty())
+                    .map(|word| word.to_lowercase())
+                    .collect();
+                if words.is_empty() {
+                    Err(format!("line {}: empty synonym list", line_no + 1))
+                } else {
+                    Ok(words)
+                }
+            };
+            match line.split_once("=>") {
+                Some((from, to)) => {
+                    let to = words(to)?;
+                    for from in words(from)? {
+                        set.add(from, &to);
+                    }
+                }
+                None => {
+                    let group = words(line)?;
+                    for word in &group {
+                        set.add(word.clone(), &group);
+                    }
+                }
+            }
+        }
+        Ok(set)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|reason| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), reason),
+            )
+        })
+    }
+
+    // 同一个词出现在多行时合并
+    fn add(&mut self, word: String, synonyms: &[String]) {
+        let group = self.groups.entry(word).or_default();
+        for synonym in synonyms {
+            if !group.contains(synonym) {
+                group.push(synonym.clone());
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn get(&self, word: &str) -> Option<&[String]> {
+        if STOPWORDS_CMN.contains(&word) {
+            return None;
+        }
+        self.groups.get(word).map(Vec::as_slice)
+    }
+
+    // 改写查询字符串，没有命中词典时原样返回；带查询语法的词不改写
+    pub fn expand(&self, jieba: &Jieba, keyword: &str) -> String {
+        if self.is_empty() {
+            return keyword.to_string();
+        }
+        let mut words = Vec::new();
+        for word in keyword.split_whitespace() {
+            if !is_plain_word(word) {
+                words.push(word.to_string());
+                continue;
+            }
+            let tokens = jieba.cut(word, true);
+            let mut parts = Vec::new();
+            let mut matched = false;
+            let mut i = 0;
+            while i < tokens.len() {
+                // 从当前位置开始取最长的命中
+                let longest = (i + 1..=tokens.len().min(i + MAX_JOINED_TOKENS))
+                    .rev()
+                    .find_map(|end| {
+                        let joined = tokens[i..end].concat().to_lowercase();
+                        self.get(&joined).map(|group| (end, group))
+                    });
+                match longest {
+                    Some((end, group)) => {
+                        parts.push(or_group(group));
+                        matched = true;
+                        i = end;
+                    }
+                    None => {
+                        parts.push(tokens[i].to_string());
+                        i += 1;
+                    }
+                }
+            }
+            if matched {
+                // 去掉切词产生的空白和标点
+                words.extend(
+                    parts
+                        .into_iter()
+                        .filter(|part| part.chars().any(char::is_alphanumeric)),
+                );
+            } else {
+                words.push(word.to_string());
+            }
+        }
+        words.join(" ")
+    }
+}
+
+// 多个词的同义词按短语匹配
+fn or_group(group: &[String]) -> String {
+    let alternatives: Vec<String> = group
+        .iter()
+        .map(|word| {
+            let word = word.replace('"', "");
+            if word.contains(' ') {
+                format!("\"{}\"", word)
+            } else {
+                word
+            }
+        })
+        .collect();
+    if alternatives.len() == 1 {
+        return alternatives[0].clone();
+    }
+    format!("({})", alternatives.join(" OR "))
+}
+
+// 所有命名词典，reload 时重新读取配置的文件；通过 set 加载的词典不会被 reload 覆盖
+#[derive(Default)]
+pub struct Synonyms {
+    paths: HashMap<String, PathBuf>,
+    sets: RwLock<HashMap<String, Arc<SynonymSet>>>,
+}
+
+impl Synonyms {
+    pub fn load(paths: &HashMap<String, PathBuf>) -> io::Result<Self> {
+        let synonyms = Self {
+            paths: paths.clone(),
+            sets: RwLock::new(HashMap::new()),
+        };
+        synonyms.reload()?;
+        Ok(synonyms)
+    }
+
+    // 先读取全部文件，全部成功后再替换，任一个出错时保留原来的词典
+    pub fn reload(&self) -> io::Result<HashMap<String, usize>> {
+        let mut loaded = HashMap::new();
+        for (name, path) in &self.paths {
+            loaded.insert(name.clone(), Arc::new(SynonymSet::load(path)?));
+        }
+        let mut sets = self.sets.write().unwrap_or_else(PoisonError::into_inner);
+        sets.extend(loaded);
+        Ok(sets
+            .iter()
+            .map(|(name, set)| (name.clone(), set.len()))
+            .collect())
+    }
+
+    pub fn set(&self, name: &str, set: SynonymSet) {
+        let mut sets = self.sets.write().unwrap_or_else(PoisonError::into_inner);
+        sets.insert(name.to_string(), Arc::new(set));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<SynonymSet>> {
+        let sets = self.sets.read().unwrap_or_else(PoisonError::into_inner);
+        sets.get(name).cloned()
+    }
+}
@@ -0,0 +1,130 @@
+// tantivy 的 BoostQuery、FuzzyTermQuery 和 PhrasePrefixQuery 没有公开内部参数的方法，
+// /search/explain 无法展示；这里包一层保留参数，打分时交给 tantivy 原来的实现
+use tantivy::query::{
+    BoostWeight, EnableScoring, FuzzyTermQuery, PhrasePrefixQuery, Query, Weight,
+};
+use tantivy::{Score, Term};
+
+// 和 BoostQuery 相同：内部是 TermQuery 时打分器仍然是 TermScorer，不影响 block-max WAND
+#[derive(Debug)]
+pub struct BoostedQuery {
+    query: Box<dyn Query>,
+    boost: Score,
+}
+
+impl BoostedQuery {
+    pub fn new(query: Box<dyn Query>, boost: Score) -> Self {
+        Self { query, boost }
+    }
+
+    pub fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+
+    pub fn boost(&self) -> Score {
+        self.boost
+    }
+}
+
+impl Clone for BoostedQuery {
+    fn clone(&self) -> Self {
+        Self {
+            query: self.query.box_clone(),
+            boost: self.boost,
+        }
+    }
+}
+
+impl Query for BoostedQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        if enable_scoring.is_scoring_enabled() {
+            Ok(Box::new(BoostWeight::new(weight, self.boost)))
+        } else {
+            Ok(weight)
+        }
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+}
+
+// 模糊匹配，prefix 为 true 时匹配以 term 开头（在编辑距离内）的词项；换位算一次编辑
+#[derive(Debug, Clone)]
+pub struct FuzzyQuery {
+    term: Term,
+    distance: u8,
+    prefix: bool,
+}
+
+impl FuzzyQuery {
+    pub fn new(term: Term, distance: u8) -> Self {
+        Self {
+            term,
+            distance,
+            prefix: false,
+        }
+    }
+
+    pub fn prefix(term: Term, distance: u8) -> Self {
+        Self {
+            term,
+            distance,
+            prefix: true,
+        }
+    }
+
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
+    pub fn distance(&self) -> u8 {
+        self.distance
+    }
+
+    pub fn is_prefix(&self) -> bool {
+        self.prefix
+    }
+}
+
+impl Query for FuzzyQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let query = if self.prefix {
+            FuzzyTermQuery::new_prefix(self.term.clone(), self.distance, true)
+        } else {
+            FuzzyTermQuery::new(self.term.clone(), self.distance, true)
+        };
+        query.weight(enable_scoring)
+    }
+}
+
+// 短语的最后一个词做前缀匹配，terms 至少两个
+#[derive(Debug, Clone)]
+pub struct PrefixPhraseQuery {
+    terms: Vec<Term>,
+}
+
+impl PrefixPhraseQuery {
+    pub fn new(terms: Vec<Term>) -> Self {
+        assert!(terms.len() > 1, "a prefix phrase needs at least two terms");
+        Self { terms }
+    }
+
+    // 包括最后的前缀
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+}
+
+impl Query for PrefixPhraseQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        PhrasePrefixQuery::new(self.terms.clone()).weight(enable_scoring)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        for term in &self.terms {
+            visitor(term, true);
+        }
+    }
+}
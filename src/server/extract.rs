@@ -0,0 +1,17 @@
+use crate::error::AppError;
+use axum::extract::{FromRequest, FromRequestParts};
+
+// 和 axum::Json 一样解析请求体，但解析失败或超过大小限制时返回 AppError 的 JSON 错误
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct JsonBody<T>(pub T);
+
+// 路径参数，例如 /docs/abc 不是数字时返回 JSON 错误而不是纯文本
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct PathParams<T>(pub T);
+
+// 查询字符串参数，缺少或类型不对时返回 JSON 错误
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct QueryParams<T>(pub T);
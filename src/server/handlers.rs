@@ -0,0 +1,573 @@
+use super::extract::{JsonBody, PathParams, QueryParams};
+use super::AppState;
+use crate::db::{Doc, ListQuery, NewDoc, Page, UpdateDoc};
+use crate::error::AppError;
+use crate::nlpcut::{cut, CutOptions, KeywordMethod};
+use crate::search::query::query_tree;
+use crate::search::service::DEFAULT_GROUP_SIZE;
+use crate::search::{DocSource, FieldBoosts, GroupBy, IndexDoc, SearchOptions, SynonymSet};
+use crate::Result;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+#[derive(Deserialize, Serialize)]
+pub struct SearchQuery {
+    pub keyword: String,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    // 宽松解析，忽略无法解析的查询片段
+    #[serde(default)]
+    pub lenient: bool,
+    // 覆盖默认字段权重，例如 `title^3 body^1`
+    pub boost: Option<String>,
+    // 多个词项用 AND 连接
+    pub conjunction: Option<bool>,
+    // 返回每个结果的得分解释
+    #[serde(default)]
+    pub explain: bool,
+    // 拉丁字母词项的最大编辑距离（0-2）
+    #[serde(default)]
+    pub fuzzy: u8,
+    // 最后一个词做前缀匹配
+    #[serde(default)]
+    pub prefix: bool,
+    // 同时按拼音匹配
+    #[serde(default)]
+    pub pinyin: bool,
+    // 没有结果时直接返回纠正后查询的结果
+    #[serde(default)]
+    pub auto_correct: bool,
+    // 同义词词典名称
+    pub synonyms: Option<String>,
+    // 近似重复的文档只返回每组得分最高的一篇
+    #[serde(default)]
+    pub collapse: bool,
+    // host 或 doc_type：limit 个组，每组 group_size 篇
+    pub group_by: Option<GroupBy>,
+    pub group_size: Option<usize>,
+    // 每个网站最多返回的文档数
+    pub max_per_host: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExplainQuery {
+    pub q: String,
+    #[serde(default)]
+    pub lenient: bool,
+    pub boost: Option<String>,
+    pub conjunction: Option<bool>,
+    #[serde(default)]
+    pub fuzzy: u8,
+    #[serde(default)]
+    pub prefix: bool,
+    #[serde(default)]
+    pub pinyin: bool,
+    pub synonyms: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SimilarQuery {
+    pub limit: Option<usize>,
+    // 默认是数据库文档
+    pub source: Option<DocSource>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct KeywordsRequest {
+    pub text: String,
+    pub top_k: Option<usize>,
+    // tfidf（默认）或 textrank
+    #[serde(default)]
+    pub method: KeywordMethod,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ClassifyRequest {
+    pub text: String,
+    pub top_k: Option<usize>,
+}
+
+// text 和 texts（批量）二选一
+#[derive(Deserialize, Serialize)]
+pub struct CutRequest {
+    pub text: Option<String>,
+    pub texts: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub options: CutOptions,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SuggestQuery {
+    pub prefix: String,
+    pub limit: Option<usize>,
+}
+
+fn field_boosts(state: &AppState, spec: Option<&str>) -> Result<Option<FieldBoosts>> {
+    spec.map(|spec| {
+        state
+            .search
+            .relevance()
+            .boosts
+            .apply(spec)
+            .map_err(|reason| AppError::bad_request_msg(&format!("invalid boost: {}", reason)))
+    })
+    .transpose()
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct InsertDoc {
+    pub title: String,
+    pub doc: String,
+    pub url: String,
+    pub id: usize,
+    #[serde(default)]
+    pub doc_type: String,
+}
+
+// 数据库中的文档按数据库 id 写入搜索索引，和 /insert 的 id 分开编号
+pub fn index_doc(doc: &Doc) -> IndexDoc {
+    IndexDoc {
+        id: doc.id as u64,
+        source: DocSource::Db,
+        title: doc.title.clone(),
+        body: doc.content.clone(),
+        url: doc.url.clone(),
+        doc_type: doc.doc_type.clone(),
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DeleteQuery {
+    pub id: u64,
+}
+
+// This is our route handler, for the route root
+// Make sure the function is `async`
+// We specify our return type, `&'static str`, however a route handler can return anything that implements `IntoResponse`
+
+pub async fn root() -> &'static str {
+    "Hello, World!"
+}
+
+// 写索引要等写锁和提交，放到阻塞线程里执行，不占用 Tokio 的工作线程
+async fn blocking<R: Send + 'static>(f: impl FnOnce() -> Result<R> + Send + 'static) -> Result<R> {
+    tokio::task::spawn_blocking(f).await?
+}
+
+pub async fn delete(
+    QueryParams(query): QueryParams<DeleteQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse> {
+    let search = state.search.clone();
+    blocking(move || search.delete(query.id)).await?;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "id": query.id,
+            "message": "delete, delete!"
+        })),
+    ))
+}
+
+pub async fn insert(
+    State(state): State<AppState>,
+    JsonBody(doc): JsonBody<InsertDoc>,
+) -> Result<impl IntoResponse> {
+    // For this route, we are going to return a Json response
+    // We create a tuple, with the first parameter being a `StatusCode`
+    // Our second parameter, is the response body, which in this example is a `Json` instance
+    // We construct data for the `Json` struct using the `serde_json::json!` macro
+    let search = state.search.clone();
+    let (doc, fingerprint) = blocking(move || {
+        let fingerprint = search.add_checked(
+            &doc.title,
+            &doc.doc,
+            Some((DocSource::Api, doc.id as u64)),
+            |_| {
+                Ok(IndexDoc {
+                    id: doc.id as u64,
+                    source: DocSource::Api,
+                    title: doc.title.clone(),
+                    body: doc.doc.clone(),
+                    url: doc.url.clone(),
+                    doc_type: doc.doc_type.clone(),
+                })
+            },
+        )?;
+        Ok((doc, fingerprint))
+    })
+    .await?;
+    let mut body = serde_json::json!({
+        "id": doc.id,
+        "title": doc.title,
+        "doc": doc.doc,
+        "message": "insert, insert!"
+    });
+    if let Some(duplicate) = fingerprint.duplicate {
+        body["duplicate_of"] = serde_json::json!(duplicate);
+    }
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// 批量导入搜索索引，请求体大小上限是 limits.bulk_body_bytes
+pub async fn insert_bulk(
+    State(state): State<AppState>,
+    JsonBody(docs): JsonBody<Vec<InsertDoc>>,
+) -> Result<impl IntoResponse> {
+    let docs: Vec<IndexDoc> = docs
+        .into_iter()
+        .map(|doc| IndexDoc {
+            id: doc.id as u64,
+            source: DocSource::Api,
+            title: doc.title,
+            body: doc.doc,
+            url: doc.url,
+            doc_type: doc.doc_type,
+        })
+        .collect();
+    // 只和已经索引的文档比较；reject 模式下跳过重复的文档，其余照常写入
+    let search = state.search.clone();
+    let (count, duplicates) = blocking(move || search.add_all_checked(&docs)).await?;
+    let duplicates: Vec<serde_json::Value> = duplicates
+        .into_iter()
+        .map(|(id, duplicate)| serde_json::json!({ "id": id, "duplicate_of": duplicate }))
+        .collect();
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "count": count,
+            "duplicates": duplicates,
+            "message": "insert, insert!"
+        })),
+    ))
+}
+
+pub async fn search(
+    QueryParams(query): QueryParams<SearchQuery>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse> {
+    // 分组、多样化和折叠都改变结果的组织方式，只能选一种
+    let modes = [
+        query.collapse,
+        query.group_by.is_some(),
+        query.max_per_host.is_some(),
+    ];
+    if modes.iter().filter(|&&mode| mode).count() > 1 {
+        return Err(AppError::bad_request_msg(
+            "collapse, group_by and max_per_host cannot be combined",
+        ));
+    }
+    let limit = state.config.pagination.clamp(query.limit);
+    state
+        .config
+        .pagination
+        .check_window(query.offset, limit)
+        .map_err(|reason| AppError::bad_request_msg(&reason))?;
+    let options = SearchOptions {
+        limit,
+        offset: query.offset,
+        lenient: query.lenient,
+        boosts: field_boosts(&state, query.boost.as_deref())?,
+        conjunction: query.conjunction,
+        explain: query.explain,
+        fuzzy: query.fuzzy,
+        prefix: query.prefix,
+        pinyin: query.pinyin,
+        auto_correct: query.auto_correct,
+        synonyms: query.synonyms.clone(),
+        collapse: query.collapse,
+        group_by: query.group_by,
+        group_size: query.group_size.map_or(DEFAULT_GROUP_SIZE, |size| {
+            state.config.pagination.clamp(Some(size))
+        }),
+        max_per_host: query
+            .max_per_host
+            .map(|size| state.config.pagination.clamp(Some(size))),
+        client: Some(client_id(&state, &headers, connect_info)),
+    };
+    let res = state.search.search_with(query.keyword.as_str(), &options)?;
+    let mut body = serde_json::json!({
+        "query": query.keyword,
+        "offset": query.offset,
+        "total": res.total,
+        "res": res.hits,
+        "message": "ok"
+    });
+    if let Some(groups) = res.groups {
+        body["groups"] = serde_json::json!(groups);
+    }
+    if !res.warnings.is_empty() {
+        body["warnings"] = serde_json::json!(res.warnings);
+    }
+    if let Some(suggestion) = res.suggestion {
+        body["suggestion"] = serde_json::json!(suggestion);
+        body["corrected"] = serde_json::json!(res.corrected);
+    }
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// 和限流一样，带凭证的请求用调用方 id，其余用客户端 IP
+fn client_id(
+    state: &AppState,
+    headers: &HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+) -> String {
+    match state.auth.authenticate(headers) {
+        Ok(Some(principal)) => principal.id,
+        _ => connect_info
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+// 返回解析后的查询树，不执行搜索；语法错误时返回 400 和出错位置
+pub async fn search_explain(
+    QueryParams(query): QueryParams<ExplainQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse> {
+    let options = SearchOptions {
+        lenient: query.lenient,
+        boosts: field_boosts(&state, query.boost.as_deref())?,
+        conjunction: query.conjunction,
+        fuzzy: query.fuzzy,
+        prefix: query.prefix,
+        pinyin: query.pinyin,
+        synonyms: query.synonyms.clone(),
+        ..Default::default()
+    };
+    let parsed = state.search.parse_query(&query.q, &options)?;
+    let schema = state.search.index().schema();
+    Ok(Json(serde_json::json!({
+        "query": query.q,
+        "lenient": query.lenient,
+        "tree": query_tree(parsed.query.as_ref(), &schema),
+        "warnings": parsed.warnings,
+    })))
+}
+
+// 搜索框输入提示：标题中的词和热门查询，按频次排序
+pub async fn suggest(
+    QueryParams(query): QueryParams<SuggestQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse> {
+    let limit = state.config.pagination.clamp(query.limit);
+    let suggestions = state.search.suggest(&query.prefix, limit)?;
+    Ok(Json(serde_json::json!({
+        "prefix": query.prefix,
+        "suggestions": suggestions,
+    })))
+}
+
+// 相关文档：默认 id 是数据库文档的 id，source=api 时是 /insert 写入的 id；结果不包含文档本身
+pub async fn similar(
+    PathParams(id): PathParams<u64>,
+    QueryParams(query): QueryParams<SimilarQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse> {
+    let limit = state.config.pagination.clamp(query.limit);
+    let hits = state
+        .search
+        .similar(query.source.unwrap_or(DocSource::Db), id, limit)?
+        .ok_or_else(AppError::notfound)?;
+    Ok(Json(serde_json::json!({ "id": id, "res": hits })))
+}
+
+// 提取文本的关键词，TF-IDF 的 IDF 来自当前索引
+pub async fn keywords(
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<KeywordsRequest>,
+) -> Result<impl IntoResponse> {
+    let top_k = state.config.pagination.clamp(req.top_k);
+    let keywords = state.search.keywords(&req.text, top_k, req.method)?;
+    Ok(Json(serde_json::json!({
+        "method": req.method,
+        "keywords": keywords,
+    })))
+}
+
+// 使用和索引相同的 Jieba 实例（包括用户词典）切词
+pub async fn cut(
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<CutRequest>,
+) -> Result<impl IntoResponse> {
+    let jieba = state.search.jieba();
+    let cut = |text: &str| {
+        cut::cut(jieba, text, &req.options).map_err(|reason| AppError::bad_request_msg(&reason))
+    };
+    match (&req.text, &req.texts) {
+        (Some(text), None) => Ok(Json(serde_json::json!({ "words": cut(text)? }))),
+        (None, Some(texts)) => {
+            let results = texts
+                .iter()
+                .map(|text| cut(text))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Json(serde_json::json!({ "results": results })))
+        }
+        _ => Err(AppError::bad_request_msg(
+            "exactly one of `text` and `texts` is required",
+        )),
+    }
+}
+
+// 用离线训练的分类模型给文本分类，返回概率最高的几个分类
+pub async fn classify(
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<ClassifyRequest>,
+) -> Result<impl IntoResponse> {
+    let classifier = state
+        .classifier
+        .as_ref()
+        .ok_or_else(|| AppError::unavailable_msg("no classifier model is configured"))?;
+    let mut predictions = classifier.classify(state.search.jieba(), &req.text);
+    predictions.truncate(state.config.pagination.clamp(req.top_k));
+    Ok(Json(serde_json::json!({
+        "label": predictions.first().map(|prediction| &prediction.label),
+        "predictions": predictions,
+    })))
+}
+
+// 重新读取配置的同义词文件，返回每个词典的词条数
+pub async fn reload_synonyms(State(state): State<AppState>) -> Result<impl IntoResponse> {
+    let sets = state.search.synonyms().reload()?;
+    tracing::info!(?sets, "synonyms reloaded");
+    Ok(Json(serde_json::json!({ "sets": sets })))
+}
+
+// 用请求体（Solr 格式的文本）替换或新建一个同义词词典，只保存在内存中
+pub async fn put_synonyms(
+    PathParams(name): PathParams<String>,
+    State(state): State<AppState>,
+    body: String,
+) -> Result<impl IntoResponse> {
+    let set = SynonymSet::parse(&body)
+        .map_err(|reason| AppError::bad_request_msg(&format!("invalid synonyms: {}", reason)))?;
+    let entries = set.len();
+    state.search.synonyms().set(&name, set);
+    tracing::info!(name, entries, "synonyms updated");
+    Ok(Json(
+        serde_json::json!({ "name": name, "entries": entries }),
+    ))
+}
+
+pub async fn feed(
+    QueryParams(mut query): QueryParams<ListQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Page<Doc>>> {
+    let limit = state
+        .config
+        .pagination
+        .clamp(query.limit.map(|limit| limit.max(0) as usize));
+    query.limit = Some(limit as i64);
+    Ok(Json(state.docs.list(query).await?))
+}
+
+pub async fn get_doc(
+    PathParams(id): PathParams<i32>,
+    State(state): State<AppState>,
+) -> Result<Json<Doc>> {
+    let res = state.docs.get(id).await?;
+    res.map(Json).ok_or_else(AppError::notfound)
+}
+
+pub async fn update_doc(
+    PathParams(id): PathParams<i32>,
+    State(state): State<AppState>,
+    JsonBody(mut changes): JsonBody<UpdateDoc>,
+) -> Result<Json<Doc>> {
+    // 标题或正文变了要重新计算指纹，写入索引时直接使用
+    let mut fingerprint = None;
+    if changes.title.is_some() || changes.content.is_some() {
+        let doc = state.docs.get(id).await?.ok_or_else(AppError::notfound)?;
+        let title = changes.title.as_deref().unwrap_or(&doc.title);
+        let content = changes.content.as_deref().unwrap_or(&doc.content);
+        let computed =
+            state
+                .search
+                .fingerprint(title, content, Some((DocSource::Db, id as u64)))?;
+        changes.simhash = Some(computed.simhash as i64);
+        fingerprint = Some(computed);
+    }
+    let doc = state
+        .docs
+        .update(id, changes)
+        .await?
+        .ok_or_else(AppError::notfound)?;
+    let search = state.search.clone();
+    let indexed = index_doc(&doc);
+    blocking(move || match fingerprint {
+        Some(fingerprint) => search.add_with(&indexed, fingerprint),
+        None => search.add(&indexed),
+    })
+    .await?;
+    Ok(Json(doc))
+}
+
+pub async fn delete_doc(
+    PathParams(id): PathParams<i32>,
+    State(state): State<AppState>,
+) -> Result<StatusCode> {
+    if state.docs.delete(id).await? {
+        let search = state.search.clone();
+        blocking(move || search.delete_from(DocSource::Db, id as u64)).await?;
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::notfound())
+    }
+}
+
+pub async fn insert_doc(
+    State(state): State<AppState>,
+    JsonBody(mut doc): JsonBody<NewDoc>,
+) -> Result<Json<Doc>> {
+    // 没有给出 doc_type 时按标题和正文自动分类
+    if doc.doc_type.trim().is_empty() {
+        if let Some(classifier) = &state.classifier {
+            let text = format!("{}\n{}", doc.title, doc.content);
+            if let Some(prediction) = classifier.predict(state.search.jieba(), &text) {
+                doc.doc_type = prediction.label;
+            }
+        }
+    }
+    // 重复检测、写数据库和写索引在同一把写锁下完成；数据库写入是异步的，放到阻塞线程里等待
+    let search = state.search.clone();
+    let docs = state.docs.clone();
+    let runtime = tokio::runtime::Handle::current();
+    let doc = blocking(move || {
+        let title = doc.title.clone();
+        let content = doc.content.clone();
+        let mut created = None;
+        let added = search.add_checked(&title, &content, None, |fingerprint| {
+            doc.simhash = Some(fingerprint.simhash as i64);
+            // 只有和数据库文档重复时才记录，/insert 写入的文档不在数据库中
+            doc.duplicate_of = fingerprint
+                .duplicate
+                .filter(|duplicate| duplicate.source == DocSource::Db)
+                .and_then(|duplicate| i32::try_from(duplicate.id).ok());
+            let doc = created.insert(runtime.block_on(docs.create(doc))?);
+            Ok(index_doc(doc))
+        });
+        match (added, created) {
+            (Ok(_), Some(doc)) => Ok(doc),
+            // 数据库已经写入但索引失败，删掉这一行，避免数据库里有搜不到的文档
+            (Err(err), Some(doc)) => {
+                if let Err(delete_err) = runtime.block_on(docs.delete(doc.id)) {
+                    tracing::error!(id = doc.id, error = %delete_err, "failed to roll back document");
+                }
+                Err(err)
+            }
+            (Err(err), None) => Err(err),
+            (Ok(_), None) => unreachable!("add_checked writes only after prepare succeeds"),
+        }
+    })
+    .await?;
+    Ok(Json(doc))
+}
@@ -0,0 +1,59 @@
+use super::AppState;
+use crate::metrics;
+use axum::{extract::State, http::header, http::StatusCode, response::IntoResponse, Json};
+
+// 进程存活即返回 200
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+// 文档存储可以取到连接且迁移已执行、索引 reader 可用时返回 200，否则 503
+pub async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    let store = state.docs.check().await;
+    let index = state.search.check_reader();
+
+    let status = if store.is_ok() && index.is_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    // 探针不需要认证，错误详情（连接串、路径等）只写日志
+    let describe = |check: &str, res: &crate::Result<()>| match res {
+        Ok(()) => "ok",
+        Err(err) => {
+            tracing::warn!(check, error = %err, "readiness check failed");
+            "fail"
+        }
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "ready": status == StatusCode::OK,
+            "checks": {
+                "store": describe("store", &store),
+                "index": describe("index", &index),
+            }
+        })),
+    )
+}
+
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let stats = state.search.stats();
+    metrics::INDEX_DOCS.set(stats.num_docs as i64);
+    metrics::INDEX_SEGMENTS.set(stats.num_segments as i64);
+    let pool = state.docs.pool_status();
+    metrics::DB_POOL
+        .with_label_values(&["max"])
+        .set(pool.max_size as f64);
+    metrics::DB_POOL
+        .with_label_values(&["size"])
+        .set(pool.size as f64);
+    metrics::DB_POOL
+        .with_label_values(&["available"])
+        .set(pool.available as f64);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(),
+    )
+}
@@ -0,0 +1,253 @@
+// HTTP 服务：路由和处理函数，二进制和其他服务都可以通过 build_router 使用
+pub mod extract;
+pub mod handlers;
+pub mod health;
+
+use crate::auth::{self, Authenticator};
+use crate::config::Config;
+use crate::db::{self, DocumentStore};
+use crate::metrics;
+use crate::nlpcut::{load_jieba, Classifier};
+use crate::ratelimit::{self, Limiter};
+use crate::search::{SearchService, Synonyms};
+use crate::Result;
+use axum::extract::DefaultBodyLimit;
+use axum::http::Request;
+use axum::middleware;
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
+use tower_http::LatencyUnit;
+use tracing::{Level, Span};
+
+pub use handlers::{
+    ClassifyRequest, DeleteQuery, ExplainQuery, InsertDoc, KeywordsRequest, SearchQuery,
+    SimilarQuery, SuggestQuery,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Config>,
+    pub search: SearchService,
+    pub docs: Arc<dyn DocumentStore>,
+    pub auth: Arc<Authenticator>,
+    pub limiter: Arc<Limiter>,
+    // 没有配置模型文件时为 None
+    pub classifier: Option<Arc<Classifier>>,
+}
+
+// 按配置设置词典、归一化、权重和重复检测，服务启动和重建索引共用
+pub fn configure_search(config: &Config, search: SearchService) -> Result<SearchService> {
+    let jieba = Arc::new(load_jieba(&config.tokenizer.dict_paths)?);
+    Ok(search
+        .with_jieba(jieba)
+        .with_normalizers(config.tokenizer.normalize)
+        .with_writer_memory(config.index.writer_memory)
+        .with_query_limits(config.limits.query_limits())
+        .with_relevance(config.search.relevance())
+        .with_dedup(config.dedup)
+        .with_popular(config.search.popular)
+        .with_synonyms(Arc::new(Synonyms::load(&config.search.synonyms)?)))
+}
+
+impl AppState {
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let search = configure_search(
+            config,
+            match &config.index.path {
+                Some(path) => SearchService::open_or_create(path)?,
+                None => SearchService::create_in_tempdir()?,
+            },
+        )?;
+
+        let classifier = match &config.classifier.path {
+            Some(path) => {
+                let classifier = Classifier::load(path)?;
+                tracing::info!(path = %path.display(), labels = ?classifier.labels(), "classifier loaded");
+                Some(Arc::new(classifier))
+            }
+            None => None,
+        };
+
+        // set up the document store and run the migrations on server startup
+        let docs = db::connect(&config.database).await?;
+
+        let auth = Authenticator::from_config(&config.auth)?;
+        if auth.allow_anonymous() {
+            tracing::warn!("auth.allow_anonymous is set, write and admin endpoints are open");
+        } else if !auth.enabled() {
+            tracing::warn!(
+                "no api keys or jwt configured, write and admin endpoints reject all requests"
+            );
+        }
+
+        Ok(Self {
+            config: Arc::new(config.clone()),
+            search,
+            docs,
+            auth: Arc::new(auth),
+            limiter: Arc::new(Limiter::from_config(&config.limits)),
+            classifier,
+        })
+    }
+}
+
+impl AppState {
+    // 提交索引并关闭连接池，在服务停止接收请求之后调用
+    pub async fn shutdown(&self) -> Result<()> {
+        let search = self.search.clone();
+        tokio::task::spawn_blocking(move || search.shutdown())
+            .await
+            .expect("index shutdown task panicked")?;
+        self.docs.close();
+        Ok(())
+    }
+}
+
+pub async fn build_router(config: Config) -> Result<Router> {
+    let state = AppState::from_config(&config).await?;
+    Ok(router(state))
+}
+
+pub fn router(state: AppState) -> Router {
+    let limits = &state.config.limits;
+    // 写接口需要 write 角色
+    let write = Router::new()
+        .route("/insert", post(handlers::insert))
+        .route(
+            "/insert/bulk",
+            post(handlers::insert_bulk).layer(DefaultBodyLimit::max(limits.bulk_body_bytes)),
+        )
+        .route("/delete", delete(handlers::delete))
+        .route(
+            "/docs/:id",
+            put(handlers::update_doc).delete(handlers::delete_doc),
+        )
+        .route("/insert_doc", post(handlers::insert_doc))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_write,
+        ));
+
+    // 管理接口需要 admin 角色
+    let admin = Router::new()
+        .route("/admin/synonyms/reload", post(handlers::reload_synonyms))
+        .route("/admin/synonyms/:name", put(handlers::put_synonyms))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_admin,
+        ));
+
+    Router::new()
+        .route("/", get(handlers::root))
+        .route("/search", get(handlers::search))
+        .route("/search/explain", get(handlers::search_explain))
+        .route("/suggest", get(handlers::suggest))
+        .route("/nlp/cut", post(handlers::cut))
+        .route("/nlp/keywords", post(handlers::keywords))
+        .route("/nlp/classify", post(handlers::classify))
+        .route("/feed", get(handlers::feed))
+        .route("/docs/:id", get(handlers::get_doc))
+        .route("/docs/:id/similar", get(handlers::similar))
+        .merge(write)
+        .merge(admin)
+        // 健康检查和指标接口不限流，在限流之后添加
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            ratelimit::rate_limit,
+        ))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/metrics", get(health::metrics))
+        .route_layer(middleware::from_fn(metrics::track_requests))
+        .layer(DefaultBodyLimit::max(limits.body_bytes))
+        .with_state(state)
+        // 最后添加的 layer 最先执行：先生成或沿用 X-Request-Id，再记录请求日志，最后把 id 写回响应头
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(request_span)
+                .on_response(
+                    DefaultOnResponse::new()
+                        .level(Level::INFO)
+                        .latency_unit(LatencyUnit::Millis),
+                ),
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+}
+
+fn request_span<B>(req: &Request<B>) -> Span {
+    let request_id = req
+        .headers()
+        .get("x-request-id")
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri(),
+        request_id = %request_id,
+    )
+}
+
+// 监听 SIGINT/SIGTERM，收到后停止接收新请求，等待处理中的请求完成（最多 shutdown_timeout 秒），
+// 然后提交索引并关闭连接池
+pub async fn serve(state: AppState, addr: SocketAddr) -> Result<()> {
+    let drain_timeout = Duration::from_secs(state.config.server.shutdown_timeout);
+    let signalled = Arc::new(Notify::new());
+    let server = axum::Server::try_bind(&addr)?
+        // 按客户端 IP 限流需要连接地址
+        .serve(router(state.clone()).into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown({
+            let signalled = signalled.clone();
+            async move {
+                shutdown_signal().await;
+                tracing::info!("shutdown signal received, draining in-flight requests");
+                signalled.notify_one();
+            }
+        });
+    tracing::info!("listening on {}", addr);
+
+    tokio::select! {
+        res = server => res?,
+        _ = async {
+            signalled.notified().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            tracing::warn!("requests still in flight after {:?}, shutting down anyway", drain_timeout);
+        }
+    }
+
+    state.shutdown().await?;
+    tracing::info!("index committed and store closed");
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,64 @@
+use axum::http::HeaderMap;
+use rust_starter::auth::{Authenticator, Role};
+use rust_starter::config::{ApiKeyConfig, AuthConfig};
+use rust_starter::error::AppErrorType;
+use sha2::{Digest, Sha256};
+
+fn api_key(name: &str, key: &str, roles: Vec<Role>) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: name.to_string(),
+        key_sha256: hex::encode(Sha256::digest(key.as_bytes())),
+        roles,
+    }
+}
+
+fn headers(key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", key.parse().unwrap());
+    headers
+}
+
+#[test]
+fn api_keys_are_checked_against_roles() {
+    let auth = Authenticator::from_config(&AuthConfig {
+        api_keys: vec![
+            api_key("reader", "r-key", vec![Role::Read]),
+            api_key("ops", "a-key", vec![Role::Admin]),
+        ],
+        jwt: None,
+        allow_anonymous: false,
+    })
+    .unwrap();
+
+    let err = auth.authorize(&HeaderMap::new(), Role::Write).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::Unauthorized));
+    let err = auth.authorize(&headers("nope"), Role::Write).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::Unauthorized));
+    let err = auth.authorize(&headers("r-key"), Role::Write).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::Forbidden));
+
+    // admin 包含 write
+    let principal = auth.authorize(&headers("a-key"), Role::Write).unwrap();
+    assert_eq!(principal.unwrap().id, "ops");
+}
+
+#[test]
+fn closed_without_credentials() {
+    // 没有配置凭证时写接口和管理接口不对匿名请求开放
+    let auth = Authenticator::from_config(&AuthConfig::default()).unwrap();
+    assert!(!auth.enabled());
+    let err = auth.authorize(&HeaderMap::new(), Role::Admin).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::Unauthorized));
+    let err = auth.authorize(&headers("any"), Role::Write).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::Unauthorized));
+
+    let auth = Authenticator::from_config(&AuthConfig {
+        allow_anonymous: true,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(auth
+        .authorize(&HeaderMap::new(), Role::Admin)
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,55 @@
+use jieba_rs::Jieba;
+use rust_starter::crawler::read_corpus_from;
+use rust_starter::nlpcut::Classifier;
+
+const SAMPLES: &[(&str, &str)] = &[
+    ("news_sports", "国足世预赛主场战胜对手，球迷欢呼"),
+    ("news_sports", "NBA总决赛湖人队夺冠，詹姆斯获得FMVP"),
+    ("news_sports", "中超联赛第十轮，球队客场绝杀对手"),
+    ("news_tech", "苹果发布新款手机，芯片性能大幅提升"),
+    ("news_tech", "人工智能芯片创业公司完成新一轮融资"),
+    ("news_tech", "5G手机出货量增长，国产芯片加速替代"),
+];
+
+#[test]
+fn naive_bayes_classifies_and_round_trips() {
+    let jieba = Jieba::new();
+    let classifier = Classifier::train(&jieba, SAMPLES.iter().copied(), 1);
+    assert_eq!(classifier.labels(), ["news_sports", "news_tech"]);
+
+    let prediction = classifier.predict(&jieba, "新手机搭载自研芯片").unwrap();
+    assert_eq!(prediction.label, "news_tech");
+    assert!(prediction.probability > 0.5);
+    let predictions = classifier.classify(&jieba, "球队主场战胜对手");
+    assert_eq!(predictions[0].label, "news_sports");
+    let total: f64 = predictions.iter().map(|p| p.probability).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    // 没有词表中的词时不做判断
+    assert!(classifier.predict(&jieba, "的了吗").is_none());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("classifier.json");
+    classifier.save(&path).unwrap();
+    let loaded = Classifier::load(&path).unwrap();
+    assert_eq!(loaded.classify(&jieba, "球队主场战胜对手"), predictions);
+    std::fs::write(&path, "not json").unwrap();
+    assert!(Classifier::load(&path).is_err());
+}
+
+#[test]
+fn corpus_lines_keep_categories() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("news.txt"),
+        "6552368441838272771_!_101_!_news_culture_!_发酵床的垫料种类有哪些？_!_\n\
+         6552407065758663427_!_102_!_news_entertainment_!_谢娜为李浩菲澄清网络谣言_!_谢娜,李浩菲\n\
+         _!_标题_!_关键词\n",
+    )
+    .unwrap();
+    let lines = read_corpus_from(&[dir.path().to_path_buf()]);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].category.as_deref(), Some("news_culture"));
+    assert_eq!(lines[1].title, "谢娜为李浩菲澄清网络谣言");
+    assert_eq!(lines[1].keywords, "谢娜,李浩菲");
+    assert_eq!(lines[2].category, None);
+}
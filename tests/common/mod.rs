@@ -0,0 +1,32 @@
+// 集成测试共用的文档和索引，每个测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use rust_starter::search::{DocSource, IndexDoc, SearchService};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+// /insert 写入的文档，url 按 id 生成；其他字段用结构体更新语法覆盖
+pub fn doc(id: u64, title: &str, body: &str) -> IndexDoc {
+    IndexDoc {
+        id,
+        source: DocSource::Api,
+        title: title.to_string(),
+        body: body.to_string(),
+        url: format!("https://example.com/{}", id),
+        doc_type: String::new(),
+    }
+}
+
+// 写入 docs 并提交的内存索引
+pub fn indexed(docs: &[IndexDoc]) -> SearchService {
+    let service = SearchService::create_in_ram().unwrap();
+    service.add_all(docs).unwrap();
+    service
+}
+
+// 临时目录下的索引路径，TempDir 释放时删除
+pub fn temp_index() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("index");
+    (dir, path)
+}
@@ -0,0 +1,133 @@
+use rust_starter::config::{Cli, Config, ConfigError, StoreBackend};
+use std::collections::HashMap;
+use std::io::Write;
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+#[test]
+fn file_env_and_cli_override_in_order() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+[server]
+bind = "127.0.0.1:8080"
+
+[database]
+url = "postgres://file/db"
+pool_size = 4
+
+[pagination]
+default_page_size = 5
+max_page_size = 50
+"#
+    )
+    .unwrap();
+    let cli = Cli {
+        config: Some(file.path().to_path_buf()),
+        pool_size: Some(8),
+        ..Default::default()
+    };
+    let config =
+        Config::from_sources(cli, &env(&[("PORT", "9000"), ("APP_DB_POOL_SIZE", "2")])).unwrap();
+    assert_eq!(config.addr().to_string(), "127.0.0.1:9000");
+    assert_eq!(config.database.url.as_deref(), Some("postgres://file/db"));
+    assert_eq!(config.database.pool_size, 8);
+    assert_eq!(config.pagination.clamp(None), 5);
+    assert_eq!(config.pagination.clamp(Some(500)), 50);
+    assert!(config.pagination.check_window(9_950, 50).is_ok());
+    assert!(config.pagination.check_window(9_951, 50).is_err());
+    assert!(config.pagination.check_window(usize::MAX, 50).is_err());
+}
+
+#[test]
+fn backend_is_inferred_from_url() {
+    let config = Config::from_sources(Cli::default(), &env(&[])).unwrap();
+    assert_eq!(config.database.backend(), StoreBackend::Sqlite);
+    assert_eq!(config.database.sqlite_path(), "docs.db");
+
+    let config =
+        Config::from_sources(Cli::default(), &env(&[("DATABASE_URL", "postgres://x/db")])).unwrap();
+    assert_eq!(config.database.backend(), StoreBackend::Postgres);
+
+    let err =
+        Config::from_sources(Cli::default(), &env(&[("APP_DB_BACKEND", "postgres")])).unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::Invalid {
+            key: "database.url",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn invalid_values_are_reported() {
+    let err = Config::from_sources(
+        Cli::default(),
+        &env(&[("DATABASE_URL", "postgres://x/db"), ("PORT", "abc")]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ConfigError::Env { key: "PORT", .. }));
+
+    let err = Config::from_sources(
+        Cli {
+            writer_memory: Some(1_000),
+            ..Default::default()
+        },
+        &env(&[("DATABASE_URL", "postgres://x/db")]),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid `index.writer_memory`: must be at least 15000000 bytes"
+    );
+}
+
+#[test]
+fn result_window_can_be_set_on_the_command_line() {
+    let cli = Cli {
+        max_result_window: Some(1_000),
+        ..Default::default()
+    };
+    let config = Config::from_sources(cli, &env(&[("APP_MAX_RESULT_WINDOW", "5000")])).unwrap();
+    assert!(config.pagination.check_window(900, 100).is_ok());
+    assert!(config.pagination.check_window(901, 100).is_err());
+
+    // 不能小于 max_page_size
+    let cli = Cli {
+        max_result_window: Some(10),
+        ..Default::default()
+    };
+    let err = Config::from_sources(cli, &env(&[])).unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::Invalid {
+            key: "pagination.max_result_window",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn dedup_threshold_is_capped_by_the_bands() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(file, "[dedup]\nmode = \"reject\"\nthreshold = 4").unwrap();
+    let cli = Cli {
+        config: Some(file.path().to_path_buf()),
+        ..Default::default()
+    };
+    let err = Config::from_sources(cli, &env(&[])).unwrap_err();
+    let ConfigError::Invalid { key, reason } = err else {
+        panic!("unexpected error: {}", err);
+    };
+    assert_eq!(key, "dedup.threshold");
+    assert!(reason.contains("between 0 and 3"), "{}", reason);
+    assert!(reason.contains("4 bands of 16 bits"), "{}", reason);
+}
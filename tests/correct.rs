@@ -0,0 +1,51 @@
+mod common;
+
+use common::doc;
+use rust_starter::search::correct::levenshtein;
+use rust_starter::search::{SearchOptions, SearchService};
+
+fn service() -> SearchService {
+    common::indexed(&[
+        doc(1, "谢娜主持快乐大本营", "节目 television show"),
+        doc(2, "谢娜的新歌", "television music"),
+        doc(3, "search engine", "tantivy is a search engine library"),
+    ])
+}
+
+#[test]
+fn did_you_mean_suggests_corrected_query() {
+    assert_eq!(levenshtein("televsion", "television"), 1);
+    assert_eq!(levenshtein("", "abc"), 3);
+
+    let service = service();
+    // 英文按编辑距离纠正
+    let res = service.search("televsion", 10, 0).unwrap();
+    assert_eq!(res.total, 0);
+    assert_eq!(res.suggestion.as_deref(), Some("television"));
+    assert!(!res.corrected);
+
+    // 中文按同音词纠正，拼音输入也可以纠正成中文
+    let res = service.search("谢那", 10, 0).unwrap();
+    assert_eq!(res.suggestion.as_deref(), Some("谢娜"));
+    let res = service.search("xiena", 10, 0).unwrap();
+    assert_eq!(res.suggestion.as_deref(), Some("谢娜"));
+
+    // 有结果或者找不到更好的查询时不给建议
+    assert_eq!(service.search("search", 10, 0).unwrap().suggestion, None);
+    assert_eq!(
+        service.search("qwertyuiop", 10, 0).unwrap().suggestion,
+        None
+    );
+    assert_eq!(service.correct("title:televsion").unwrap(), None);
+
+    // auto_correct 直接返回纠正后的结果；默认 OR 时 "engine" 已经有结果，所以用 AND
+    let options = SearchOptions {
+        conjunction: Some(true),
+        auto_correct: true,
+        ..Default::default()
+    };
+    let res = service.search_with("serch engine", &options).unwrap();
+    assert!(res.corrected);
+    assert_eq!(res.suggestion.as_deref(), Some("search engine"));
+    assert_eq!(res.hits[0].id, 3);
+}
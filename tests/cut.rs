@@ -0,0 +1,78 @@
+use jieba_rs::Jieba;
+use rust_starter::nlpcut::cut::cut;
+use rust_starter::nlpcut::{CutMode, CutOptions};
+
+const TEXT: &str = "我们在中国科学院学习";
+
+fn words(jieba: &Jieba, text: &str, options: CutOptions) -> Vec<String> {
+    cut(jieba, text, &options)
+        .unwrap()
+        .into_iter()
+        .map(|word| word.word)
+        .collect()
+}
+
+#[test]
+fn cut_modes_pos_and_stopwords() {
+    let jieba = Jieba::new();
+    let options = CutOptions::default();
+    assert_eq!(
+        words(&jieba, TEXT, options),
+        ["我们", "在", "中国科学院", "学习"]
+    );
+    let search = words(
+        &jieba,
+        TEXT,
+        CutOptions {
+            mode: CutMode::CutForSearch,
+            ..options
+        },
+    );
+    assert!(search.contains(&"科学".to_string()));
+    assert!(search.contains(&"中国科学院".to_string()));
+    let all = words(
+        &jieba,
+        TEXT,
+        CutOptions {
+            mode: CutMode::CutAll,
+            ..options
+        },
+    );
+    assert!(all.contains(&"科学院".to_string()));
+
+    // 停用词和空白被去掉
+    let res = words(
+        &jieba,
+        "我们 在 学习",
+        CutOptions {
+            remove_stopwords: true,
+            ..options
+        },
+    );
+    assert_eq!(res, ["学习"]);
+
+    let tagged = cut(
+        &jieba,
+        TEXT,
+        &CutOptions {
+            pos: true,
+            ..options
+        },
+    )
+    .unwrap();
+    assert_eq!(tagged[0].tag.as_deref(), Some("r"));
+    assert_eq!(tagged[2].word, "中国科学院");
+    assert_eq!(tagged[2].tag.as_deref(), Some("nt"));
+
+    // 词性只支持 cut 模式
+    assert!(cut(
+        &jieba,
+        TEXT,
+        &CutOptions {
+            mode: CutMode::CutAll,
+            pos: true,
+            ..options
+        }
+    )
+    .is_err());
+}
@@ -0,0 +1,223 @@
+// SQLite 后端总是测试；Postgres 的测试默认忽略，需要数据库（CI 中单独运行）：
+// DATABASE_URL=postgres://... cargo test --test db -- --ignored
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel_migrations::MigrationHarness;
+use rust_starter::db::{
+    postgres, DocumentStore, ListQuery, NewDoc, Order, PgStore, SqliteStore, UpdateDoc,
+};
+use std::sync::Once;
+
+static MIGRATE: Once = Once::new();
+
+// 运行被忽略的测试时必须设置 DATABASE_URL，不能静默跳过
+fn pg_store() -> PgStore {
+    let db_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL must point to a Postgres database to run the Postgres tests");
+    // 测试并行运行，迁移只跑一次
+    MIGRATE.call_once(|| {
+        let mut conn = PgConnection::establish(&db_url).unwrap();
+        conn.run_pending_migrations(postgres::MIGRATIONS).unwrap();
+    });
+    PgStore::new(postgres::pool(&db_url, 4).unwrap())
+}
+
+// 每个测试用自己的 SQLite 文件；tempdir 需要活到测试结束
+async fn sqlite_store() -> (SqliteStore, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("docs.db");
+    let sqlite = SqliteStore::connect(path.to_str().unwrap(), 4)
+        .await
+        .unwrap();
+    (sqlite, dir)
+}
+
+fn new_doc(title: &str, doc_type: &str, published: Option<bool>) -> NewDoc {
+    NewDoc {
+        title: title.to_string(),
+        url: format!("https://example.com/{}", title),
+        content: "北京大学".to_string(),
+        doc_type: doc_type.to_string(),
+        published,
+        simhash: None,
+        duplicate_of: None,
+    }
+}
+
+// 每个测试用独立的 doc_type，避免并行测试互相干扰
+fn unique_type(name: &str) -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("test_{}_{}", name, nanos)
+}
+
+#[derive(QueryableByName, Debug, PartialEq)]
+struct Column {
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    data_type: String,
+    #[diesel(sql_type = Text)]
+    is_nullable: String,
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn schema_matches_migrations() {
+    let store = pg_store();
+    let conn = store.pool().get().await.unwrap();
+    let columns = conn
+        .interact(|conn| {
+            diesel::sql_query(
+                "SELECT column_name::text, data_type::text, is_nullable::text \
+                 FROM information_schema.columns \
+                 WHERE table_name = 'docs' ORDER BY ordinal_position",
+            )
+            .load::<Column>(conn)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let nullable_column = |name: &str, data_type: &str, nullable: &str| Column {
+        column_name: name.to_string(),
+        data_type: data_type.to_string(),
+        is_nullable: nullable.to_string(),
+    };
+    let column = |name: &str, data_type: &str| nullable_column(name, data_type, "NO");
+    // 与 src/schema.rs 保持一致
+    assert_eq!(
+        columns,
+        vec![
+            column("id", "integer"),
+            column("title", "character varying"),
+            column("url", "character varying"),
+            column("content", "text"),
+            column("doc_type", "character varying"),
+            column("published", "boolean"),
+            nullable_column("simhash", "bigint", "YES"),
+            nullable_column("duplicate_of", "integer", "YES"),
+        ]
+    );
+}
+
+async fn crud_roundtrip(store: &dyn DocumentStore) {
+    let doc_type = unique_type("crud");
+    let created = store.create(new_doc("a", &doc_type, None)).await.unwrap();
+    assert!(!created.published);
+    assert_eq!(store.get(created.id).await.unwrap(), Some(created.clone()));
+
+    let updated = store
+        .update(
+            created.id,
+            UpdateDoc {
+                published: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(updated.published);
+    assert_eq!(updated.title, created.title);
+
+    assert!(store.delete(created.id).await.unwrap());
+    assert!(!store.delete(created.id).await.unwrap());
+    assert_eq!(store.get(created.id).await.unwrap(), None);
+    assert!(store
+        .update(created.id, UpdateDoc::default())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn list_pages_with_cursor(store: &dyn DocumentStore) {
+    let doc_type = unique_type("list");
+    let mut ids = Vec::new();
+    for (i, published) in [true, false, true, true, true].into_iter().enumerate() {
+        let doc = new_doc(&i.to_string(), &doc_type, Some(published));
+        ids.push(store.create(doc).await.unwrap().id);
+    }
+
+    let query = ListQuery {
+        limit: Some(2),
+        doc_type: Some(doc_type.clone()),
+        published: Some(true),
+        ..Default::default()
+    };
+    let first = store.list(query.clone()).await.unwrap();
+    let first_ids: Vec<i32> = first.docs.iter().map(|d| d.id).collect();
+    assert_eq!(first_ids, vec![ids[0], ids[2]]);
+    assert_eq!(first.next_cursor, Some(ids[2]));
+
+    let second = store
+        .list(ListQuery {
+            after_id: first.next_cursor,
+            ..query.clone()
+        })
+        .await
+        .unwrap();
+    let second_ids: Vec<i32> = second.docs.iter().map(|d| d.id).collect();
+    assert_eq!(second_ids, vec![ids[3], ids[4]]);
+    assert_eq!(second.next_cursor, None);
+
+    let desc = store
+        .list(ListQuery {
+            order: Order::Desc,
+            ..query
+        })
+        .await
+        .unwrap();
+    assert_eq!(desc.docs[0].id, ids[4]);
+}
+
+#[tokio::test]
+async fn sqlite_crud_roundtrip() {
+    let (store, _dir) = sqlite_store().await;
+    crud_roundtrip(&store).await;
+}
+
+#[derive(QueryableByName)]
+struct BusyTimeout {
+    #[diesel(sql_type = BigInt)]
+    timeout: i64,
+}
+
+#[tokio::test]
+async fn sqlite_connections_wait_for_locks() {
+    let (store, _dir) = sqlite_store().await;
+    // 同时取两个连接，第二个是新建的；busy_timeout 在连接创建时设置
+    let conns = [
+        store.pool().get().await.unwrap(),
+        store.pool().get().await.unwrap(),
+    ];
+    for conn in conns {
+        let res = conn
+            .interact(|conn| {
+                diesel::sql_query("PRAGMA busy_timeout").get_result::<BusyTimeout>(conn)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.timeout, 5000);
+    }
+}
+
+#[tokio::test]
+async fn sqlite_list_pages_with_cursor() {
+    let (store, _dir) = sqlite_store().await;
+    list_pages_with_cursor(&store).await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn postgres_crud_roundtrip() {
+    crud_roundtrip(&pg_store()).await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn postgres_list_pages_with_cursor() {
+    list_pages_with_cursor(&pg_store()).await;
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use common::doc;
+use jieba_rs::Jieba;
+use rust_starter::error::{AppError, AppErrorType};
+use rust_starter::nlpcut::simhash::{bands, hamming, simhash};
+use rust_starter::search::{Dedup, DedupMode, DocSource, IndexDoc, SearchOptions, SearchService};
+
+const ORIGINAL: &str = "国足世预赛主场二比零战胜对手，武磊梅开二度，球迷在看台上欢呼庆祝，\
+                        主教练赛后表示球队会继续努力备战下一场客场比赛";
+// 转载时改了几个字
+const REPOST: &str = "国足世预赛主场二比零战胜对手，武磊梅开二度，球迷在看台上欢呼庆祝，\
+                      主教练赛后表示球队将继续努力备战下一场客场比赛！";
+const OTHER: &str = "苹果发布新款手机，自研芯片性能大幅提升，续航时间比上一代更长";
+
+fn service(mode: DedupMode) -> SearchService {
+    SearchService::create_in_ram()
+        .unwrap()
+        .with_dedup(Dedup { mode, threshold: 3 })
+}
+
+#[test]
+fn simhash_is_close_for_reposts() {
+    let jieba = Jieba::new();
+    let original = simhash(&jieba, ORIGINAL);
+    assert_eq!(original, simhash(&jieba, ORIGINAL));
+    assert!(hamming(original, simhash(&jieba, REPOST)) <= 3);
+    assert!(hamming(original, simhash(&jieba, OTHER)) > 10);
+    // 没有有效词
+    assert_eq!(simhash(&jieba, "的了吗，。"), 0);
+}
+
+#[test]
+fn close_fingerprints_share_a_band() {
+    let fingerprint = 0x0123_4567_89ab_cdef;
+    // 每段改一位，4 段都不同
+    let far = fingerprint ^ (1 | 1 << 16 | 1 << 32 | 1 << 48);
+    assert!(bands(fingerprint).zip(bands(far)).all(|(a, b)| a != b));
+    // 改 3 位最多影响 3 段
+    let close = fingerprint ^ (1 | 1 << 20 | 1 << 40);
+    assert!(bands(fingerprint).zip(bands(close)).any(|(a, b)| a == b));
+    // 不同段的值不会相同
+    let mut zero: Vec<u64> = bands(0).collect();
+    zero.dedup();
+    assert_eq!(zero.len(), 4);
+}
+
+#[test]
+fn find_duplicate_follows_mode() {
+    let off = service(DedupMode::Off);
+    off.add(&doc(1, "国足战胜对手", ORIGINAL)).unwrap();
+    let fingerprint = off.simhash("国足战胜对手", REPOST);
+    assert_eq!(off.find_duplicate(fingerprint, None).unwrap(), None);
+
+    let flag = service(DedupMode::Flag);
+    flag.add_all(&[doc(1, "国足战胜对手", ORIGINAL), doc(2, "新手机", OTHER)])
+        .unwrap();
+    let duplicate = flag.find_duplicate(fingerprint, None).unwrap().unwrap();
+    assert_eq!((duplicate.id, duplicate.group), (1, 1));
+    assert!(duplicate.distance <= 3);
+    // 更新文档时不和自己比较
+    assert_eq!(
+        flag.find_duplicate(fingerprint, Some((DocSource::Api, 1)))
+            .unwrap(),
+        None
+    );
+    // 删除的文档不再参与比较
+    flag.delete(1).unwrap();
+    assert_eq!(flag.find_duplicate(fingerprint, None).unwrap(), None);
+}
+
+#[test]
+fn group_mode_collapses_duplicates_in_search() {
+    let service = service(DedupMode::Group);
+    service.add(&doc(1, "国足战胜对手", ORIGINAL)).unwrap();
+    service.add(&doc(2, "国足战胜对手", REPOST)).unwrap();
+    service.add(&doc(3, "国足战胜对手", ORIGINAL)).unwrap();
+    service
+        .add(&doc(4, "国足新闻", "国足公布新一期集训名单"))
+        .unwrap();
+    // 重复的文档归到第一篇的组
+    let duplicate = service
+        .find_duplicate(
+            service.simhash("国足战胜对手", REPOST),
+            Some((DocSource::Api, 2)),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(duplicate.group, 1);
+
+    let all = service.search("国足", 10, 0).unwrap();
+    assert_eq!(all.total, 4);
+    assert!(all.hits.iter().all(|hit| hit.duplicates.is_none()));
+
+    let options = SearchOptions {
+        limit: 10,
+        collapse: true,
+        ..Default::default()
+    };
+    let collapsed = service.search_with("国足", &options).unwrap();
+    assert_eq!(collapsed.total, 2);
+    assert_eq!(collapsed.hits.len(), 2);
+    let mut duplicates: Vec<Option<usize>> =
+        collapsed.hits.iter().map(|hit| hit.duplicates).collect();
+    duplicates.sort();
+    assert_eq!(duplicates, [Some(0), Some(2)]);
+
+    let second_page = SearchOptions {
+        offset: 1,
+        ..options
+    };
+    let page = service.search_with("国足", &second_page).unwrap();
+    assert_eq!(page.total, 2);
+    assert_eq!(page.hits.len(), 1);
+}
+
+#[test]
+fn api_and_db_docs_use_separate_keys() {
+    let service = service(DedupMode::Group);
+    service.add(&doc(1, "国足战胜对手", ORIGINAL)).unwrap();
+    service
+        .add(&IndexDoc {
+            source: DocSource::Db,
+            ..doc(1, "新手机", OTHER)
+        })
+        .unwrap();
+    // 相同的 id 不会互相覆盖
+    assert_eq!(service.search("国足", 10, 0).unwrap().total, 1);
+    assert_eq!(service.search("手机", 10, 0).unwrap().total, 1);
+
+    // 数据库文档的重复组号和 /insert 的 id 不冲突
+    let fingerprint = service.simhash("新手机", OTHER);
+    let duplicate = service.find_duplicate(fingerprint, None).unwrap().unwrap();
+    assert_eq!((duplicate.id, duplicate.source), (1, DocSource::Db));
+    assert_ne!(duplicate.group, 1);
+    assert_eq!(
+        service
+            .find_duplicate(fingerprint, Some((DocSource::Db, 1)))
+            .unwrap(),
+        None
+    );
+
+    service.delete(1).unwrap();
+    assert_eq!(service.search("国足", 10, 0).unwrap().total, 0);
+    let hits = service.search("手机", 10, 0).unwrap().hits;
+    assert_eq!((hits[0].id, hits[0].source), (1, DocSource::Db));
+    service.delete_from(DocSource::Db, 1).unwrap();
+    assert_eq!(service.search("手机", 10, 0).unwrap().total, 0);
+}
+
+#[test]
+fn reject_mode_checks_and_adds_under_one_lock() {
+    let service = service(DedupMode::Reject);
+    // 并发写入近似重复的文档，只有一篇能写入
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (1..=4)
+            .map(|id| {
+                let service = &service;
+                let body = if id % 2 == 0 { ORIGINAL } else { REPOST };
+                scope.spawn(move || {
+                    service.add_checked("国足战胜对手", body, None, |_| {
+                        Ok(doc(id, "国足战胜对手", body))
+                    })
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(results.iter().filter(|res| res.is_ok()).count(), 1);
+    for err in results.into_iter().filter_map(Result::err) {
+        assert!(matches!(err.types, AppErrorType::Conflict));
+        assert!(err.details.unwrap()["duplicate_of"].is_u64());
+    }
+    assert_eq!(service.search("国足", 10, 0).unwrap().total, 1);
+
+    // prepare 出错时不写入
+    let err = service
+        .add_checked("新手机", OTHER, None, |_| {
+            Err(AppError::db_msg("connection lost"))
+        })
+        .err()
+        .unwrap();
+    assert!(matches!(err.types, AppErrorType::Db));
+    assert_eq!(service.search("手机", 10, 0).unwrap().total, 0);
+
+    // 批量写入时跳过和已提交文档重复的
+    let (count, duplicates) = service
+        .add_all_checked(&[doc(10, "国足战胜对手", REPOST), doc(11, "新手机", OTHER)])
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].0, 10);
+    assert_eq!(service.search("国足", 10, 0).unwrap().total, 1);
+    assert_eq!(service.search("手机", 10, 0).unwrap().total, 1);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use rust_starter::nlpcut::url::host;
+use rust_starter::search::{GroupBy, IndexDoc, SearchOptions, SearchService};
+
+fn doc(id: u64, title: &str, url: &str, doc_type: &str) -> IndexDoc {
+    IndexDoc {
+        url: url.to_string(),
+        doc_type: doc_type.to_string(),
+        ..common::doc(id, title, "")
+    }
+}
+
+fn service() -> SearchService {
+    let service = common::indexed(&[
+        doc(1, "北京 北京 北京", "https://a.com/1", "news_tech"),
+        doc(2, "北京 北京", "https://A.com:8080/2", "news_tech"),
+        doc(3, "北京", "https://a.com/3", "news_sports"),
+        doc(4, "北京 北京 北京 天安门", "https://b.com/4", "news_sports"),
+        doc(5, "北京欢迎你", "https://user@c.com/5?q=1", ""),
+    ]);
+    // 分到不同的段，验证跨段合并
+    service
+        .add(&doc(6, "北京 北京 北京", "https://b.com/6", "news_tech"))
+        .unwrap();
+    service
+}
+
+#[test]
+fn host_strips_scheme_userinfo_and_port() {
+    assert_eq!(host("https://User@GitHub.com:443/rust?q=1"), "github.com");
+    assert_eq!(host("example.com/path"), "example.com");
+    assert_eq!(host(""), "");
+}
+
+#[test]
+fn group_by_returns_top_hits_per_group() {
+    let service = service();
+    let options = SearchOptions {
+        group_by: Some(GroupBy::Host),
+        group_size: 2,
+        ..Default::default()
+    };
+    let res = service.search_with("北京", &options).unwrap();
+    assert_eq!(res.total, 3);
+    assert!(res.hits.is_empty());
+    let groups = res.groups.unwrap();
+    let keys: Vec<&str> = groups.iter().map(|group| group.key.as_str()).collect();
+    assert_eq!(keys.len(), 3);
+    let a = groups.iter().find(|group| group.key == "a.com").unwrap();
+    assert_eq!(a.total, 3);
+    let ids: Vec<u64> = a.hits.iter().map(|hit| hit.id).collect();
+    assert_eq!(ids, [1, 2]);
+    let b = groups.iter().find(|group| group.key == "b.com").unwrap();
+    assert_eq!(b.total, 2);
+    // 组按最高得分排序
+    assert!(groups
+        .windows(2)
+        .all(|pair| pair[0].hits[0].score >= pair[1].hits[0].score));
+
+    let page = SearchOptions {
+        limit: 1,
+        offset: 1,
+        group_by: Some(GroupBy::DocType),
+        ..Default::default()
+    };
+    let res = service.search_with("北京", &page).unwrap();
+    assert_eq!(res.total, 3);
+    assert_eq!(res.groups.unwrap().len(), 1);
+    let res = service
+        .search_with(
+            "北京",
+            &SearchOptions {
+                group_by: Some(GroupBy::DocType),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let mut keys: Vec<(String, usize)> = res
+        .groups
+        .unwrap()
+        .into_iter()
+        .map(|group| (group.key, group.total))
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            (String::new(), 1),
+            ("news_sports".to_string(), 2),
+            ("news_tech".to_string(), 3),
+        ]
+    );
+}
+
+#[test]
+fn max_per_host_caps_hits_per_site() {
+    let service = service();
+    let options = SearchOptions {
+        max_per_host: Some(1),
+        ..Default::default()
+    };
+    let res = service.search_with("北京", &options).unwrap();
+    assert_eq!(res.total, 3);
+    assert!(res.groups.is_none());
+    let hosts: Vec<String> = res.hits.iter().map(|hit| host(&hit.url)).collect();
+    assert_eq!(hosts.len(), 3);
+    assert!(hosts.contains(&"a.com".to_string()));
+    assert!(hosts.contains(&"b.com".to_string()));
+    assert!(hosts.contains(&"c.com".to_string()));
+    assert!(res
+        .hits
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+
+    // 按网站过滤
+    let res = service.search("host:b.com", 10, 0).unwrap();
+    assert_eq!(res.total, 2);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::doc;
+use jieba_rs::Jieba;
+use rust_starter::nlpcut::keywords::{candidates, textrank, tfidf};
+use rust_starter::nlpcut::{Keyword, KeywordMethod};
+
+const TEXT: &str =
+    "搜索引擎使用倒排索引。倒排索引记录每个词出现在哪些文档中，搜索引擎根据倒排索引找到文档。";
+
+fn words(keywords: &[Keyword]) -> Vec<&str> {
+    keywords
+        .iter()
+        .map(|keyword| keyword.word.as_str())
+        .collect()
+}
+
+#[test]
+fn tfidf_and_textrank_rank_frequent_words() {
+    let jieba = Jieba::new();
+    let candidates = candidates(&jieba, TEXT);
+    // 停用词、标点和单字被过滤
+    assert!(!candidates.contains(&"哪些"));
+    assert!(!candidates.contains(&"。"));
+    assert!(candidates.iter().all(|word| word.chars().count() >= 2));
+
+    let res = tfidf(&candidates, 2, |_| 1.0);
+    assert_eq!(words(&res), ["倒排", "索引"]);
+    // IDF 高的词排在前面
+    let res = tfidf(
+        &candidates,
+        1,
+        |word| if word == "文档" { 10.0 } else { 1.0 },
+    );
+    assert_eq!(words(&res), ["文档"]);
+
+    let res = textrank(&candidates, 3);
+    assert_eq!(res.len(), 3);
+    assert_eq!(res[0].weight, 1.0);
+    assert!(words(&res).contains(&"索引"));
+    assert!(textrank(&[], 3).is_empty());
+}
+
+#[test]
+fn keywords_use_index_idf_and_are_stored() {
+    let service = common::indexed(&[
+        doc(1, "文档一", "这是一篇普通文档"),
+        doc(2, "文档二", "另一篇普通文档"),
+    ]);
+    // "文档" 在索引中很常见，IDF 低
+    let res = service
+        .keywords("普通文档介绍倒排索引", 1, KeywordMethod::TfIdf)
+        .unwrap();
+    assert_ne!(res[0].word, "文档");
+
+    service.add(&doc(3, "倒排索引", TEXT)).unwrap();
+    let res = service.search("keywords:倒排", 10, 0).unwrap();
+    assert_eq!(res.hits[0].id, 3);
+    assert!(res.hits[0].keywords.contains(&"倒排".to_string()));
+}
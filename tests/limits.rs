@@ -0,0 +1,75 @@
+use rust_starter::config::Quota;
+use rust_starter::error::AppErrorType;
+use rust_starter::ratelimit::{RateLimiter, MAX_BUCKETS};
+use rust_starter::search::QueryLimits;
+use std::time::{Duration, Instant};
+
+#[test]
+fn token_bucket_refills_over_time() {
+    let limiter = RateLimiter::new(Quota {
+        rate: 2.0,
+        burst: 3,
+    });
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert!(limiter.check_at("1.2.3.4", start).is_ok());
+    }
+    let wait = limiter.check_at("1.2.3.4", start).unwrap_err();
+    assert_eq!(wait, Duration::from_millis(500));
+    // 其他客户端有自己的桶
+    assert!(limiter.check_at("5.6.7.8", start).is_ok());
+
+    let later = start + Duration::from_millis(500);
+    assert!(limiter.check_at("1.2.3.4", later).is_ok());
+    assert!(limiter.check_at("1.2.3.4", later).is_err());
+
+    let disabled = RateLimiter::new(Quota {
+        rate: 0.0,
+        burst: 0,
+    });
+    for _ in 0..100 {
+        assert!(disabled.check_at("1.2.3.4", start).is_ok());
+    }
+}
+
+#[test]
+fn idle_and_excess_buckets_are_evicted() {
+    let limiter = RateLimiter::new(Quota {
+        rate: 1.0,
+        burst: 2,
+    });
+    let start = Instant::now();
+    for i in 0..MAX_BUCKETS + 100 {
+        assert!(limiter.check_at(&i.to_string(), start).is_ok());
+    }
+    // 超过上限时淘汰最久没有使用的桶
+    assert_eq!(limiter.tracked(), MAX_BUCKETS);
+    assert!(limiter.check_at("busy", start).is_ok());
+    assert!(limiter.check_at("busy", start).is_ok());
+    assert!(limiter.check_at("busy", start).is_err());
+
+    // 两秒后所有桶都已经回满，和新桶没有区别，全部被清理
+    let later = start + Duration::from_secs(2);
+    assert!(limiter.check_at("busy", later).is_ok());
+    assert_eq!(limiter.tracked(), 1);
+}
+
+#[test]
+fn query_limits_reject_long_and_wildcard_queries() {
+    let limits = QueryLimits {
+        max_len: 20,
+        max_terms: 3,
+    };
+    assert!(limits.check("北京 AND 大学").is_ok());
+    assert!(limits.check("title:(北京 大学) -清华").is_ok());
+
+    let rejected = |query: &str| {
+        let err = limits.check(query).unwrap_err();
+        assert!(matches!(err.types, AppErrorType::BadRequest), "{}", query);
+    };
+    rejected("北京大学北京大学北京大学北京大学北京大学北京大学");
+    rejected("a b c d");
+    rejected("*京");
+    rejected("title:?京");
+    rejected("+(*京)");
+}
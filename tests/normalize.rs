@@ -0,0 +1,82 @@
+mod common;
+
+use jieba_rs::Jieba;
+use rust_starter::nlpcut::{JiebaTokenizer, Normalizer};
+use rust_starter::search::{FieldNormalizers, IndexDoc, SearchService};
+use std::sync::Arc;
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+
+fn doc(id: u64, title: &str, url: &str) -> IndexDoc {
+    IndexDoc {
+        url: url.to_string(),
+        ..common::doc(id, title, "")
+    }
+}
+
+#[test]
+fn normalizer_folds_variants() {
+    let normalizer = Normalizer::default();
+    assert_eq!(normalizer.normalize("臺灣大學"), "台湾大学");
+    assert_eq!(normalizer.normalize("ＡＢＣ　１２３"), "abc 123");
+    // NFKC：合字和兼容字符展开，组合字符合并
+    assert_eq!(normalizer.normalize("ﬁle ㎏"), "file kg");
+    assert_eq!(normalizer.normalize("Cafe\u{301}"), "café");
+    assert_eq!(Normalizer::disabled().normalize("臺灣ＡＢＣ"), "臺灣ＡＢＣ");
+    let lowercase_only = Normalizer {
+        lowercase: true,
+        ..Normalizer::disabled()
+    };
+    assert_eq!(lowercase_only.normalize("臺灣ABC"), "臺灣abc");
+}
+
+#[test]
+fn token_filter_keeps_original_offsets() {
+    let jieba = JiebaTokenizer::new(Arc::new(Jieba::new()));
+    let mut analyzer = TextAnalyzer::builder(jieba)
+        .filter(Normalizer::default())
+        .build();
+    let text = "ＲＵＳＴ語言";
+    let mut stream = analyzer.token_stream(text);
+    let mut tokens = Vec::new();
+    while let Some(token) = stream.next() {
+        tokens.push((
+            token.text.clone(),
+            &text[token.offset_from..token.offset_to],
+        ));
+    }
+    assert_eq!(
+        tokens,
+        [
+            ("rust".to_string(), "ＲＵＳＴ"),
+            ("语言".to_string(), "語言")
+        ]
+    );
+}
+
+#[test]
+fn index_and_query_are_normalized_per_field() {
+    let service = SearchService::create_in_ram().unwrap();
+    service
+        .add_all(&[doc(1, "臺灣旅遊指南", "https://example.com/Taiwan")])
+        .unwrap();
+    assert_eq!(service.search("台湾", 10, 0).unwrap().hits[0].id, 1);
+    assert_eq!(service.search("ＴＡＩＷＡＮ", 10, 0).unwrap().total, 1);
+    // 原文按原样保存
+    assert_eq!(
+        service.search("臺灣", 10, 0).unwrap().hits[0].title,
+        "臺灣旅遊指南"
+    );
+
+    // url 字段关闭归一化后区分大小写
+    let service = SearchService::create_in_ram()
+        .unwrap()
+        .with_normalizers(FieldNormalizers {
+            url: Normalizer::disabled(),
+            ..Default::default()
+        });
+    service
+        .add(&doc(1, "旅游指南", "https://example.com/Taiwan"))
+        .unwrap();
+    assert_eq!(service.search("url:taiwan", 10, 0).unwrap().total, 0);
+    assert_eq!(service.search("url:Taiwan", 10, 0).unwrap().total, 1);
+}
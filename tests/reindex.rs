@@ -0,0 +1,52 @@
+mod common;
+
+use common::doc;
+use rust_starter::search::{DocSource, IndexDoc, SearchService};
+
+#[test]
+fn schema_version_mismatch_fails_and_stored_docs_survive() {
+    let (_dir, path) = common::temp_index();
+    let service = SearchService::open_or_create(&path).unwrap();
+    service
+        .add_all(&[
+            doc(1, "北京天气", ""),
+            IndexDoc {
+                source: DocSource::Db,
+                ..doc(1, "上海天气", "")
+            },
+        ])
+        .unwrap();
+    service.shutdown().unwrap();
+    drop(service);
+
+    // 版本一致时可以重新打开
+    let service = SearchService::open_or_create(&path).unwrap();
+    assert_eq!(service.search("天气", 10, 0).unwrap().total, 2);
+    drop(service);
+
+    // 加上版本号之前创建的索引没有版本文件
+    std::fs::remove_file(path.join("schema_version")).unwrap();
+    let err = SearchService::open_or_create(&path).err().unwrap();
+    // 启动失败时 main 打印 Display 输出，应该是可读的错误信息而不是结构体
+    let printed = err.to_string();
+    assert!(printed.starts_with("search index at "), "{}", printed);
+    let message = err.message.unwrap();
+    assert!(message.contains("schema version 0"), "{}", message);
+    assert!(message.contains("reindex"), "{}", message);
+
+    let stored = SearchService::stored_docs(&path).unwrap();
+    assert!(stored.has_source);
+    let mut docs: Vec<(u64, DocSource, String)> = stored
+        .docs
+        .into_iter()
+        .map(|doc| (doc.id, doc.source, doc.title))
+        .collect();
+    docs.sort_by_key(|doc| doc.2.clone());
+    assert_eq!(
+        docs,
+        [
+            (1, DocSource::Db, "上海天气".to_string()),
+            (1, DocSource::Api, "北京天气".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,95 @@
+// 相关性评测：在人工标注的小数据集上计算 nDCG@5，调整权重或 BM25 参数前后对比
+// cargo test --test relevance -- --nocapture 查看每个查询的得分
+use rust_starter::search::{
+    Bm25Params, FieldBoosts, IndexDoc, Relevance, SearchOptions, SearchService,
+};
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+
+const K: usize = 5;
+
+#[derive(Deserialize)]
+struct Judged {
+    docs: Vec<IndexDoc>,
+    queries: Vec<JudgedQuery>,
+}
+
+#[derive(Deserialize)]
+struct JudgedQuery {
+    query: String,
+    // 文档 id -> 相关度（0-3）
+    judgments: HashMap<u64, u32>,
+}
+
+fn dcg(grades: impl Iterator<Item = u32>) -> f64 {
+    grades
+        .enumerate()
+        .map(|(rank, grade)| (2f64.powi(grade as i32) - 1.0) / (rank as f64 + 2.0).log2())
+        .sum()
+}
+
+fn ndcg(ranked: &[u64], judgments: &HashMap<u64, u32>) -> f64 {
+    let actual = dcg(ranked
+        .iter()
+        .take(K)
+        .map(|id| judgments.get(id).copied().unwrap_or(0)));
+    let mut ideal: Vec<u32> = judgments.values().copied().collect();
+    ideal.sort_unstable_by(|a, b| b.cmp(a));
+    let ideal = dcg(ideal.into_iter().take(K));
+    if ideal == 0.0 {
+        0.0
+    } else {
+        actual / ideal
+    }
+}
+
+// 返回所有查询的平均 nDCG@5
+fn evaluate(name: &str, relevance: Relevance) -> f64 {
+    let judged: Judged = serde_json::from_str(include_str!("fixtures/relevance.json")).unwrap();
+    let service = SearchService::create_in_ram()
+        .unwrap()
+        .with_relevance(relevance);
+    service.add_all(&judged.docs).unwrap();
+
+    let mut total = 0.0;
+    for query in &judged.queries {
+        let options = SearchOptions {
+            limit: K,
+            ..Default::default()
+        };
+        let res = service.search_with(&query.query, &options).unwrap();
+        let ranked: Vec<u64> = res.hits.iter().map(|hit| hit.id).collect();
+        let score = ndcg(&ranked, &query.judgments);
+        println!("{:<12} {:<12} {:.3} {:?}", name, query.query, score, ranked);
+        total += score;
+    }
+    let mean = total / judged.queries.len() as f64;
+    println!("{:<12} mean nDCG@{} = {:.3}", name, K, mean);
+    mean
+}
+
+#[test]
+fn default_relevance_beats_flat_boosts() {
+    let flat = evaluate(
+        "flat",
+        Relevance {
+            boosts: FieldBoosts {
+                title: 1.0,
+                body: 1.0,
+                url: 1.0,
+                pinyin: 1.0,
+            },
+            ..Default::default()
+        },
+    );
+    let tuned = evaluate("default", Relevance::default());
+    evaluate(
+        "b=0",
+        Relevance {
+            bm25: Bm25Params { k1: 1.2, b: 0.0 },
+            ..Default::default()
+        },
+    );
+    assert!(tuned >= flat, "default {:.3} < flat {:.3}", tuned, flat);
+    assert!(tuned >= 0.95, "default relevance regressed: {:.3}", tuned);
+}
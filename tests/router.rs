@@ -0,0 +1,338 @@
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use rust_starter::auth::Role;
+use rust_starter::config::{ApiKeyConfig, Config, Quota};
+use rust_starter::server::{router, AppState};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn api_key(name: &str, key: &str, roles: Vec<Role>) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: name.to_string(),
+        key_sha256: hex::encode(Sha256::digest(key.as_bytes())),
+        roles,
+    }
+}
+
+// read、write、admin 三个 key，数据库是临时目录下的 SQLite，索引也在临时目录
+fn config() -> Config {
+    let mut config = Config::default();
+    config.auth.api_keys = vec![
+        api_key("reader", "r-key", vec![Role::Read]),
+        api_key("writer", "w-key", vec![Role::Write]),
+        api_key("ops", "a-key", vec![Role::Admin]),
+    ];
+    config
+}
+
+async fn app(mut config: Config) -> (TempDir, AppState) {
+    let dir = TempDir::new().unwrap();
+    config.database.url = Some(dir.path().join("docs.db").display().to_string());
+    let state = AppState::from_config(&config).await.unwrap();
+    (dir, state)
+}
+
+fn request(method: Method, uri: &str, key: Option<&str>, body: Option<Value>) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        builder = builder.header("x-api-key", key);
+    }
+    match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+async fn send(app: &Router, req: Request<Body>) -> (StatusCode, Value) {
+    let res = app.clone().oneshot(req).await.unwrap();
+    let status = res.status();
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+fn insert_body(id: u64) -> Value {
+    serde_json::json!({
+        "id": id,
+        "title": "北京天气",
+        "doc": "今天晴",
+        "url": format!("https://example.com/{}", id),
+    })
+}
+
+#[tokio::test]
+async fn routes_check_roles() {
+    let (_dir, state) = app(config()).await;
+    let app = router(state);
+    let status = |req| {
+        let app = app.clone();
+        async move { send(&app, req).await.0 }
+    };
+
+    // 读接口不需要凭证
+    for key in [None, Some("r-key"), Some("w-key"), Some("a-key")] {
+        let req = request(Method::GET, "/search?keyword=%E5%8C%97%E4%BA%AC", key, None);
+        assert_eq!(status(req).await, StatusCode::OK, "{:?}", key);
+        let req = request(Method::GET, "/docs/1", key, None);
+        assert_eq!(status(req).await, StatusCode::NOT_FOUND, "{:?}", key);
+    }
+
+    // 写接口：没有凭证或凭证无效 401，角色不够 403
+    let expected = [
+        (None, StatusCode::UNAUTHORIZED),
+        (Some("bad-key"), StatusCode::UNAUTHORIZED),
+        (Some("r-key"), StatusCode::FORBIDDEN),
+        (Some("w-key"), StatusCode::OK),
+        (Some("a-key"), StatusCode::OK),
+    ];
+    for (i, (key, expected)) in expected.into_iter().enumerate() {
+        let req = request(Method::POST, "/insert", key, Some(insert_body(i as u64)));
+        assert_eq!(status(req).await, expected, "/insert {:?}", key);
+        // 通过认证之后才查数据库，不存在的文档返回 404
+        let expected = match expected {
+            StatusCode::OK => StatusCode::NOT_FOUND,
+            other => other,
+        };
+        let req = request(Method::DELETE, "/docs/1000", key, None);
+        assert_eq!(status(req).await, expected, "DELETE /docs {:?}", key);
+        let body = serde_json::json!({ "title": "新标题" });
+        let req = request(Method::PUT, "/docs/1000", key, Some(body));
+        assert_eq!(status(req).await, expected, "PUT /docs {:?}", key);
+    }
+
+    let doc = serde_json::json!({
+        "title": "北京天气",
+        "content": "今天晴",
+        "url": "https://example.com/db",
+        "doc_type": "news",
+    });
+    let req = request(
+        Method::POST,
+        "/insert_doc",
+        Some("r-key"),
+        Some(doc.clone()),
+    );
+    assert_eq!(status(req).await, StatusCode::FORBIDDEN);
+    let (code, created) = send(
+        &app,
+        request(Method::POST, "/insert_doc", Some("w-key"), Some(doc)),
+    )
+    .await;
+    assert_eq!(code, StatusCode::OK, "{}", created);
+    let uri = format!("/docs/{}", created["id"]);
+    assert_eq!(
+        status(request(Method::GET, &uri, None, None)).await,
+        StatusCode::OK
+    );
+
+    // 管理接口只有 admin 可以调用，write 也是 403
+    let expected = [
+        (None, StatusCode::UNAUTHORIZED),
+        (Some("r-key"), StatusCode::FORBIDDEN),
+        (Some("w-key"), StatusCode::FORBIDDEN),
+        (Some("a-key"), StatusCode::OK),
+    ];
+    for (key, expected) in expected {
+        let req = Request::builder()
+            .method(Method::PUT)
+            .uri("/admin/synonyms/test");
+        let req = match key {
+            Some(key) => req.header("x-api-key", key),
+            None => req,
+        };
+        let req = req.body(Body::from("番茄,西红柿")).unwrap();
+        assert_eq!(status(req).await, expected, "admin {:?}", key);
+    }
+
+    // 错误也是 JSON
+    let (status, body) = send(&app, request(Method::POST, "/insert", None, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "unauthorized");
+}
+
+#[tokio::test]
+async fn allow_anonymous_opens_write_and_admin_routes() {
+    let mut config = config();
+    config.auth.allow_anonymous = true;
+    let (_dir, state) = app(config).await;
+    let app = router(state);
+
+    let (status, body) = send(
+        &app,
+        request(Method::POST, "/insert", None, Some(insert_body(1))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let req = Request::builder()
+        .method(Method::PUT)
+        .uri("/admin/synonyms/test")
+        .body(Body::from("番茄,西红柿"))
+        .unwrap();
+    assert_eq!(send(&app, req).await.0, StatusCode::OK);
+    // 带了凭证仍然按角色检查
+    let (status, _) = send(
+        &app,
+        request(Method::POST, "/insert", Some("r-key"), Some(insert_body(2))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(
+        &app,
+        request(
+            Method::POST,
+            "/insert",
+            Some("bad-key"),
+            Some(insert_body(2)),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn rate_limit_returns_retry_after() {
+    let mut config = config();
+    config.limits.per_ip = Quota {
+        rate: 0.1,
+        burst: 2,
+    };
+    let (_dir, state) = app(config).await;
+    let app = router(state);
+    let search = || request(Method::GET, "/search?keyword=rust", None, None);
+
+    for _ in 0..2 {
+        assert_eq!(send(&app, search()).await.0, StatusCode::OK);
+    }
+    let res = app.clone().oneshot(search()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    // 每 10 秒回一个令牌
+    let retry_after = res.headers()[header::RETRY_AFTER].to_str().unwrap();
+    assert_eq!(retry_after, "10");
+    // 健康检查不限流
+    let res = app
+        .clone()
+        .oneshot(request(Method::GET, "/healthz", None, None))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    // 带凭证的请求按调用方单独计数
+    let req = request(Method::GET, "/search?keyword=rust", Some("r-key"), None);
+    assert_eq!(send(&app, req).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn body_limits_differ_for_bulk_insert() {
+    let mut config = config();
+    config.limits.body_bytes = 1024;
+    config.limits.bulk_body_bytes = 64 * 1024;
+    let (_dir, state) = app(config).await;
+    let app = router(state);
+
+    let mut doc = insert_body(1);
+    doc["doc"] = Value::from("长".repeat(1000));
+    let (status, body) = send(
+        &app,
+        request(Method::POST, "/insert", Some("w-key"), Some(doc.clone())),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["error"], "payload_too_large");
+    assert!(body["message"].is_string());
+
+    let bulk = Value::from(vec![doc]);
+    let (status, body) = send(
+        &app,
+        request(Method::POST, "/insert/bulk", Some("w-key"), Some(bulk)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["count"], 1);
+}
+
+#[tokio::test]
+async fn search_rejects_pages_past_the_result_window() {
+    let mut config = config();
+    config.pagination.max_result_window = 100;
+    let (_dir, state) = app(config).await;
+    let app = router(state);
+
+    let req = request(
+        Method::GET,
+        "/search?keyword=rust&offset=80&limit=20",
+        None,
+        None,
+    );
+    assert_eq!(send(&app, req).await.0, StatusCode::OK);
+    let req = request(
+        Method::GET,
+        "/search?keyword=rust&offset=81&limit=20",
+        None,
+        None,
+    );
+    let (status, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "bad_request");
+}
+
+#[tokio::test]
+async fn readyz_and_metrics() {
+    let (_dir, state) = app(config()).await;
+    let docs = state.docs.clone();
+    let app = router(state);
+
+    let (status, body) = send(&app, request(Method::GET, "/readyz", None, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["checks"]["store"], "ok");
+
+    let req = request(Method::GET, "/search?keyword=rust", None, None);
+    assert_eq!(send(&app, req).await.0, StatusCode::OK);
+    let res = app
+        .clone()
+        .oneshot(request(Method::GET, "/metrics", None, None))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(
+        text.contains("http_request_duration_seconds_bucket{"),
+        "{}",
+        text
+    );
+    assert!(text.contains(r#"route="/search""#), "{}", text);
+
+    // 连接池关闭后取不到连接
+    docs.close();
+    let (status, body) = send(&app, request(Method::GET, "/readyz", None, None)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["checks"]["store"], "fail");
+    assert_eq!(body["checks"]["index"], "ok");
+}
+
+#[tokio::test]
+async fn path_and_query_errors_are_json() {
+    let (_dir, state) = app(config()).await;
+    let app = router(state);
+
+    let (status, body) = send(&app, request(Method::GET, "/docs/abc", None, None)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "bad_request");
+    assert!(body["message"].as_str().unwrap().contains("Invalid URL"));
+
+    let (status, body) = send(&app, request(Method::GET, "/search", None, None)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "bad_request");
+    let req = request(Method::GET, "/search?keyword=rust&offset=-1", None, None);
+    let (status, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "bad_request");
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use common::doc;
+use rust_starter::error::AppErrorType;
+use rust_starter::search::query::query_tree;
+use rust_starter::search::{
+    Bm25Params, FieldBoosts, QueryError, Relevance, SearchOptions, SearchService,
+};
+use serde_json::json;
+
+#[test]
+fn index_search_delete() {
+    let service = SearchService::create_in_ram().unwrap();
+    service
+        .add(&doc(1, "长相思", "盘古大帝劈开了天地"))
+        .unwrap();
+    service
+        .add(&doc(2, "洛阳伽蓝记", "永宁寺，熙平元年，灵太后胡氏所立也"))
+        .unwrap();
+
+    let res = service.search("盘古", 10, 0).unwrap();
+    assert_eq!(res.total, 1);
+    let hits = res.hits;
+    assert_eq!(hits[0].id, 1);
+    assert_eq!(hits[0].title, "长相思");
+
+    // 相同 id 重新索引会替换旧文档
+    service.add(&doc(1, "长相思", "神农炎帝遍尝百草")).unwrap();
+    assert!(service.search("盘古", 10, 0).unwrap().hits.is_empty());
+
+    service.delete(2).unwrap();
+    assert!(service.search("永宁寺", 10, 0).unwrap().hits.is_empty());
+}
+
+#[test]
+fn parse_errors_and_lenient_mode() {
+    let service = SearchService::create_in_ram().unwrap();
+    service
+        .add(&doc(1, "长相思", "盘古大帝劈开了天地"))
+        .unwrap();
+
+    let err = service.search("盘古 (天地", 10, 0).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::BadRequest));
+    let details: Vec<QueryError> = serde_json::from_value(err.details.unwrap()).unwrap();
+    assert_eq!(details[0].position, Some(6));
+
+    let options = SearchOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let res = service.search_with("盘古 \"天地", &options).unwrap();
+    assert_eq!(res.total, 1);
+    assert_eq!(res.warnings.len(), 1);
+}
+
+#[test]
+fn boosts_and_bm25_params_change_scores() {
+    let service = SearchService::create_in_ram().unwrap();
+    service.add(&doc(1, "盘古", "天地")).unwrap();
+    service.add(&doc(2, "天地", "盘古大帝劈开了天地")).unwrap();
+
+    let ids = |service: &SearchService, options: &SearchOptions| -> Vec<u64> {
+        let res = service.search_with("盘古", options).unwrap();
+        res.hits.iter().map(|hit| hit.id).collect()
+    };
+    // 默认标题权重更高
+    assert_eq!(ids(&service, &SearchOptions::default()), vec![1, 2]);
+    let body_first = SearchOptions {
+        boosts: Some("title^1 body^5".parse::<FieldBoosts>().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(ids(&service, &body_first), vec![2, 1]);
+    assert!("author^2".parse::<FieldBoosts>().is_err());
+
+    // 默认 OR，conjunction 要求所有词项都出现
+    let options = SearchOptions {
+        conjunction: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(service.search_with("盘古 劈开", &options).unwrap().total, 1);
+    assert_eq!(service.search("盘古 劈开", 10, 0).unwrap().total, 2);
+
+    let score = |service: &SearchService| service.search("天地", 10, 0).unwrap().hits[0].score;
+    let tuned = service.clone().with_relevance(Relevance {
+        bm25: Bm25Params { k1: 2.0, b: 0.0 },
+        ..Default::default()
+    });
+    assert_ne!(score(&service), score(&tuned));
+}
+
+#[test]
+fn explain_matches_hit_scores() {
+    let service = SearchService::create_in_ram().unwrap();
+    service
+        .add(&doc(1, "盘古开天", "盘古大帝劈开了天地"))
+        .unwrap();
+    service.add(&doc(2, "女娲", "女娲造人")).unwrap();
+
+    let options = SearchOptions {
+        explain: true,
+        ..Default::default()
+    };
+    let res = service.search_with("盘古 女娲", &options).unwrap();
+    assert_eq!(res.total, 2);
+    for hit in &res.hits {
+        let explanation = hit.explanation.as_ref().unwrap();
+        assert!((explanation.value() - hit.score).abs() < 1e-4);
+        let json = serde_json::to_value(explanation).unwrap();
+        assert!(json.to_string().contains("idf"));
+    }
+    assert!(service.search("盘古", 10, 0).unwrap().hits[0]
+        .explanation
+        .is_none());
+}
+
+#[test]
+fn query_tree_renders_rewritten_queries() {
+    let service = SearchService::create_in_ram().unwrap();
+    let schema = service.index().schema();
+    let tree = |keyword: &str, options: SearchOptions| {
+        let parsed = service.parse_query(keyword, &options).unwrap();
+        query_tree(parsed.query.as_ref(), &schema)
+    };
+    // 默认 title 权重 2、url 权重 0.5，body 权重为 1 时不包 boost
+    assert_eq!(
+        tree("盘古", SearchOptions::default()),
+        json!({
+            "type": "boolean",
+            "clauses": [
+                {
+                    "occur": "should",
+                    "query": {
+                        "type": "boost",
+                        "boost": 2.0,
+                        "query": { "type": "term", "field": "title", "text": "盘古" },
+                    },
+                },
+                {
+                    "occur": "should",
+                    "query": { "type": "term", "field": "body", "text": "盘古" },
+                },
+                {
+                    "occur": "should",
+                    "query": {
+                        "type": "boost",
+                        "boost": 0.5,
+                        "query": { "type": "term", "field": "url", "text": "盘古" },
+                    },
+                },
+            ],
+        })
+    );
+
+    let expanded = tree(
+        "beijng",
+        SearchOptions {
+            fuzzy: 1,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        expanded["clauses"][0]["query"]["clauses"][1]["query"],
+        json!({
+            "type": "boost",
+            "boost": 1.0,
+            "query": {
+                "type": "fuzzy",
+                "field": "title_raw",
+                "text": "beijng",
+                "distance": 1,
+                "prefix": false,
+            },
+        })
+    );
+
+    let pinyin = tree(
+        "beijingda",
+        SearchOptions {
+            pinyin: true,
+            prefix: true,
+            ..Default::default()
+        },
+    );
+    let pinyin = pinyin.to_string();
+    assert!(pinyin.contains(r#""type":"phrase_prefix""#), "{}", pinyin);
+    assert!(
+        pinyin.contains(r#""terms":["bei","jing","da"]"#),
+        "{}",
+        pinyin
+    );
+    assert!(!pinyin.contains(r#""type":"other""#), "{}", pinyin);
+}
+
+#[test]
+fn fuzzy_prefix_and_pinyin_matching() {
+    let service = SearchService::create_in_ram().unwrap();
+    service
+        .add(&doc(1, "谢娜主持新节目", "快乐大本营 beijing"))
+        .unwrap();
+    service.add(&doc(2, "北京大学开学", "新生报到")).unwrap();
+
+    let ids = |keyword: &str, options: SearchOptions| -> Vec<u64> {
+        let res = service.search_with(keyword, &options).unwrap();
+        res.hits.iter().map(|hit| hit.id).collect()
+    };
+    assert!(ids("beijng", SearchOptions::default()).is_empty());
+    let fuzzy = SearchOptions {
+        fuzzy: 1,
+        ..Default::default()
+    };
+    assert_eq!(ids("beijng", fuzzy), vec![1]);
+
+    assert!(ids("北京大", SearchOptions::default()).is_empty());
+    let prefix = || SearchOptions {
+        prefix: true,
+        ..Default::default()
+    };
+    assert_eq!(ids("北京大", prefix()), vec![2]);
+    assert_eq!(ids("beiji", prefix()), vec![1]);
+
+    assert!(ids("xiena", SearchOptions::default()).is_empty());
+    let pinyin = || SearchOptions {
+        pinyin: true,
+        ..Default::default()
+    };
+    assert_eq!(ids("xiena", pinyin()), vec![1]);
+    assert_eq!(ids("谢那", pinyin()), vec![1]);
+    let pinyin_prefix = SearchOptions {
+        pinyin: true,
+        prefix: true,
+        ..Default::default()
+    };
+    assert_eq!(ids("beijingda", pinyin_prefix), vec![2]);
+
+    let too_fuzzy = SearchOptions {
+        fuzzy: 3,
+        ..Default::default()
+    };
+    assert!(service.search_with("beijng", &too_fuzzy).is_err());
+}
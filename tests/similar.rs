@@ -0,0 +1,35 @@
+mod common;
+
+use common::doc;
+use rust_starter::search::DocSource;
+
+#[test]
+fn similar_documents_exclude_the_source() {
+    let service = common::indexed(&[
+        doc(1, "谢娜主持快乐大本营", "谢娜和何炅一起主持快乐大本营"),
+        doc(
+            2,
+            "快乐大本营收视率",
+            "快乐大本营本周收视率第一，何炅表现出色",
+        ),
+        doc(3, "股市行情", "今天沪深两市股票普遍上涨"),
+        doc(4, "何炅的新节目", "何炅主持的新节目开播"),
+    ]);
+
+    let hits = service.similar(DocSource::Api, 1, 10).unwrap().unwrap();
+    let ids: Vec<u64> = hits.iter().map(|hit| hit.id).collect();
+    assert!(!ids.contains(&1));
+    assert!(!ids.contains(&3));
+    assert_eq!(ids[0], 2);
+    assert!(ids.contains(&4));
+
+    assert_eq!(
+        service
+            .similar(DocSource::Api, 1, 1)
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(service.similar(DocSource::Api, 42, 10).unwrap().is_none());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::doc;
+use rust_starter::search::{PopularThreshold, SearchOptions, SearchService};
+
+fn texts(service: &SearchService, prefix: &str) -> Vec<String> {
+    service
+        .suggest(prefix, 5)
+        .unwrap()
+        .into_iter()
+        .map(|suggestion| suggestion.text)
+        .collect()
+}
+
+#[test]
+fn suggest_completes_titles_and_popular_queries() {
+    let service = common::indexed(&[
+        doc(1, "北京大学招生简章", ""),
+        doc(2, "北京大学校园风景", ""),
+        doc(3, "北京天气预报", ""),
+        doc(4, "Rust 入门教程", ""),
+        doc(5, "Rust 所有权", ""),
+    ]);
+
+    assert!(texts(&service, "").is_empty());
+    // 中文前缀按 jieba 切分后补全最后一个词
+    let res = texts(&service, "北京大");
+    assert_eq!(res[0], "北京大学");
+    assert!(!res.contains(&"北京天气".to_string()));
+    // 英文不区分大小写，标题中出现次数多的排在前面
+    assert_eq!(texts(&service, "ru")[0], "rust");
+    assert_eq!(texts(&service, "rust 入")[0], "rust 入门");
+
+    // 有结果的搜索计入热门查询
+    for _ in 0..3 {
+        service.search("北京天气", 10, 0).unwrap();
+    }
+    service.search("北京 没有结果的查询词", 10, 0).unwrap();
+    // 次数不够的查询不会出现
+    service.search("北京大学 风景", 10, 0).unwrap();
+    let res = texts(&service, "北京");
+    assert_eq!(res[0], "北京天气");
+    assert!(res.contains(&"北京大学".to_string()));
+    assert!(service.suggest("北京", 1).unwrap().len() == 1);
+    assert!(!res.contains(&"北京大学 风景".to_string()));
+}
+
+#[test]
+fn popular_queries_need_distinct_clients() {
+    let service = SearchService::create_in_ram()
+        .unwrap()
+        .with_popular(PopularThreshold {
+            min_count: 2,
+            min_clients: 2,
+        });
+    service.add(&doc(1, "北京天气预报", "")).unwrap();
+    let search = |client: &str| {
+        let options = SearchOptions {
+            client: Some(client.to_string()),
+            ..Default::default()
+        };
+        service.search_with("北京天气 预报", &options).unwrap();
+    };
+    // 同一个客户端反复搜索不会让查询变成热门
+    for _ in 0..5 {
+        search("10.0.0.1");
+    }
+    assert!(!texts(&service, "北京天气 ").contains(&"北京天气 预报".to_string()));
+    search("10.0.0.2");
+    assert_eq!(texts(&service, "北京天气 ")[0], "北京天气 预报");
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::doc;
+use jieba_rs::Jieba;
+use rust_starter::error::AppErrorType;
+use rust_starter::search::{SearchOptions, SynonymSet, Synonyms};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const SYNONYMS: &str = "
+# 等价的词
+北京大学, 北大
+清华大学, 清华
+# 单向替换
+帝都, 京城 => 北京
+也, 亦
+";
+
+#[test]
+fn solr_format_expands_query_terms() {
+    let set = SynonymSet::parse(SYNONYMS).unwrap();
+    assert_eq!(set.get("北大").unwrap(), ["北京大学", "北大"]);
+    assert_eq!(set.get("京城").unwrap(), ["北京"]);
+    assert!(set.get("北京").is_none());
+    assert!(SynonymSet::parse("a, b =>").is_err());
+
+    let jieba = Jieba::new();
+    assert_eq!(set.expand(&jieba, "北大"), "(北京大学 OR 北大)");
+    assert_eq!(set.expand(&jieba, "帝都 天气"), "北京 天气");
+    // 停用词不扩展，带查询语法的词不改写
+    assert_eq!(set.expand(&jieba, "也"), "也");
+    assert_eq!(set.expand(&jieba, "title:北大"), "title:北大");
+}
+
+#[test]
+fn search_uses_named_synonym_sets() {
+    let service = common::indexed(&[
+        doc(1, "北京大学招生简章", ""),
+        doc(2, "清华大学招生简章", ""),
+    ]);
+    assert_eq!(service.search("北大", 10, 0).unwrap().total, 0);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("default.txt");
+    std::fs::write(&path, "北京大学, 北大\n").unwrap();
+    let synonyms = Synonyms::load(&HashMap::from([("default".to_string(), path.clone())])).unwrap();
+    let service = service.with_synonyms(Arc::new(synonyms));
+    let res = service.search("北大", 10, 0).unwrap();
+    assert_eq!(res.hits[0].id, 1);
+
+    // 按名称选择词典，不存在的词典返回 400
+    service
+        .synonyms()
+        .set("schools", SynonymSet::parse(SYNONYMS).unwrap());
+    let options = |name: &str| SearchOptions {
+        synonyms: Some(name.to_string()),
+        ..Default::default()
+    };
+    let res = service.search_with("帝都", &options("schools")).unwrap();
+    assert_eq!(res.hits[0].id, 1);
+    assert_eq!(service.search("帝都", 10, 0).unwrap().total, 0);
+    let err = service.search_with("帝都", &options("nope")).unwrap_err();
+    assert!(matches!(err.types, AppErrorType::BadRequest));
+
+    // reload 重新读取文件，文件有错误时保留原来的词典
+    std::fs::write(&path, "帝都, 京城 => 北京\n").unwrap();
+    let sets = service.synonyms().reload().unwrap();
+    assert_eq!(sets["default"], 2);
+    assert_eq!(service.search("帝都", 10, 0).unwrap().hits[0].id, 1);
+    assert_eq!(service.search("北大", 10, 0).unwrap().total, 0);
+    std::fs::write(&path, "北大 =>\n").unwrap();
+    assert!(service.synonyms().reload().is_err());
+    assert_eq!(service.search("帝都", 10, 0).unwrap().hits[0].id, 1);
+}
@@ -0,0 +1,99 @@
+use rust_starter::nlpcut::{JiebaTokenizer, MixedTokenizer, UrlTokenizer};
+use rust_starter::search::{DocSource, IndexDoc, SearchService};
+use std::sync::Arc;
+use tantivy::tokenizer::{TextAnalyzer, TokenStream, Tokenizer};
+
+fn tokens(tokenizer: &mut impl Tokenizer, text: &str) -> Vec<(String, usize, usize)> {
+    let mut stream = tokenizer.token_stream(text);
+    let mut tokens = Vec::new();
+    while let Some(token) = stream.next() {
+        tokens.push((token.text.clone(), token.offset_from, token.offset_to));
+    }
+    tokens
+}
+
+fn texts(tokens: &[(String, usize, usize)]) -> Vec<&str> {
+    tokens.iter().map(|(text, _, _)| text.as_str()).collect()
+}
+
+fn jieba() -> JiebaTokenizer {
+    JiebaTokenizer::new(Arc::new(jieba_rs::Jieba::new()))
+}
+
+#[test]
+fn mixed_tokenizer_stems_latin_runs() {
+    let mut mixed = MixedTokenizer::new(jieba());
+    let text = "Searching引擎, Running engines!";
+    let res = tokens(&mut mixed, text);
+    assert_eq!(texts(&res), ["search", "引擎", "run", "engin"]);
+    // offset 指向原文
+    assert_eq!(&text[res[0].1..res[0].2], "Searching");
+    assert_eq!(&text[res[1].1..res[1].2], "引擎");
+
+    let mut plain = MixedTokenizer::without_stemming(jieba());
+    assert_eq!(
+        texts(&tokens(&mut plain, "Running 引擎")),
+        ["Running", "引擎"]
+    );
+}
+
+#[test]
+fn url_tokenizer_splits_host_path_and_query() {
+    let parts = TextAnalyzer::from(MixedTokenizer::without_stemming(jieba()));
+    let mut url = UrlTokenizer::new(parts);
+    let text = "https://user@github.com:443/rust-lang/rust?q=%E5%8C%97%E4%BA%AC&page=2#top";
+    let res = tokens(&mut url, text);
+    assert_eq!(
+        texts(&res),
+        ["github", "com", "rust", "lang", "rust", "q", "北京", "page", "2", "top"]
+    );
+    // 解码后的词指向原文中整个参数值
+    assert_eq!(&text[res[6].1..res[6].2], "%E5%8C%97%E4%BA%AC");
+}
+
+#[test]
+fn search_matches_stems_and_url_parts() {
+    let service = SearchService::create_in_ram().unwrap();
+    service
+        .add_all(&[
+            IndexDoc {
+                id: 1,
+                source: DocSource::Api,
+                title: "Rust Engines 项目".to_string(),
+                body: "Searching engines written in Rust".to_string(),
+                url: "https://github.com/quickwit-oss/tantivy".to_string(),
+                doc_type: String::new(),
+            },
+            IndexDoc {
+                id: 2,
+                source: DocSource::Api,
+                title: "搜索引擎".to_string(),
+                body: "倒排索引".to_string(),
+                url: "https://example.com/search?lang=zh".to_string(),
+                doc_type: String::new(),
+            },
+        ])
+        .unwrap();
+    // 补全给出原文中的词而不是词干
+    assert_eq!(service.suggest("engi", 5).unwrap()[0].text, "engines");
+    assert_eq!(
+        service.suggest("rust engine", 5).unwrap()[0].text,
+        "rust engines"
+    );
+
+    let ids = |keyword: &str| -> Vec<u64> {
+        let res = service.search(keyword, 10, 0).unwrap();
+        res.hits.iter().map(|hit| hit.id).collect()
+    };
+    assert_eq!(ids("github"), [1]);
+    assert_eq!(ids("url:quickwit"), [1]);
+    assert_eq!(ids("url:zh"), [2]);
+    assert_eq!(ids("body:search"), [1]);
+    assert_eq!(ids("ENGINE"), [1]);
+
+    // 纠错同样换回原文中的词
+    assert_eq!(
+        service.correct("serching").unwrap().as_deref(),
+        Some("searching")
+    );
+}